@@ -1,5 +1,8 @@
 use core::fmt;
-use std::sync::Arc;
+use std::sync::{
+    Arc,
+    RwLock,
+};
 
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -8,17 +11,18 @@ use cab_why::Result;
 use crate::{
     Entry,
     Leaf,
+    WritableLeaf,
 };
 
 /// Creates a leaf from the given content.
-pub fn blob(content: impl Into<Bytes>) -> impl Leaf {
+pub fn blob(content: impl Into<Bytes>) -> impl WritableLeaf {
     Blob {
-        content: content.into(),
+        content: RwLock::new(content.into()),
     }
 }
 
 struct Blob {
-    content: Bytes,
+    content: RwLock<Bytes>,
 }
 
 impl fmt::Display for Blob {
@@ -32,11 +36,23 @@ impl Entry for Blob {
     async fn as_leaf(self: Arc<Self>) -> Option<Arc<dyn Leaf>> {
         Some(self)
     }
+
+    async fn as_writable_leaf(self: Arc<Self>) -> Option<Arc<dyn WritableLeaf>> {
+        Some(self)
+    }
 }
 
 #[async_trait]
 impl Leaf for Blob {
     async fn read(self: Arc<Self>) -> Result<Bytes> {
-        Ok(self.content.clone())
+        Ok(self.content.read().unwrap().clone())
+    }
+}
+
+#[async_trait]
+impl WritableLeaf for Blob {
+    async fn write(self: Arc<Self>, content: Bytes) -> Result<()> {
+        *self.content.write().unwrap() = content;
+        Ok(())
     }
 }
@@ -1,10 +1,23 @@
 use std::{
+    collections::HashMap,
+    ffi::{
+        self,
+        OsString,
+    },
     fmt,
+    io,
     path::{
+        self,
         Path,
         PathBuf,
     },
-    sync::Arc,
+    process,
+    sync::{
+        Arc,
+        Mutex,
+        Weak,
+        atomic,
+    },
 };
 
 use async_once_cell::OnceCell;
@@ -14,7 +27,10 @@ use cab_why::{
     Contextful as _,
     bail,
 };
-use tokio::fs;
+use tokio::{
+    fs,
+    io::AsyncWriteExt as _,
+};
 
 use crate::{
     Collection,
@@ -22,22 +38,20 @@ use crate::{
     Entry,
     Leaf,
     Result,
+    WritableCollection,
+    WritableLeaf,
     display,
 };
 
 /// Creates an entry from a given fs path.
-pub fn fs(path: PathBuf) -> impl Leaf + CollectionList {
-    FsEntry {
-        location: FsEntryLocation::Root { path },
-
-        content: OnceCell::new(),
-    }
+pub fn fs(path: PathBuf) -> impl WritableLeaf + WritableCollection + CollectionList {
+    FsEntry::new(FsEntryLocation::Root { path })
 }
 
 #[derive(Clone)]
 enum FsEntryContent {
     Leaf(Bytes),
-    CollectionList(Arc<[Arc<dyn Entry>]>),
+    CollectionList(Arc<[Arc<FsEntry>]>),
 }
 
 enum FsEntryLocation {
@@ -48,7 +62,13 @@ enum FsEntryLocation {
 struct FsEntry {
     location: FsEntryLocation,
 
-    content: OnceCell<Result<FsEntryContent>>,
+    /// The memoized content. Replaced with an empty cell when the entry is
+    /// written to, so the next read fetches the content again.
+    content: Mutex<Arc<OnceCell<Result<FsEntryContent>>>>,
+
+    /// The children that are alive, so that every handle to the same child
+    /// shares its memoized content.
+    children: Mutex<HashMap<String, Weak<FsEntry>>>,
 }
 
 impl fmt::Display for FsEntry {
@@ -76,6 +96,14 @@ impl Entry for FsEntry {
         }
     }
 
+    fn local_path(&self) -> Option<PathBuf> {
+        Some(self.path())
+    }
+
+    fn invalidate(&self) {
+        *self.content.lock().unwrap() = Arc::new(OnceCell::new());
+    }
+
     // TODO: Maybe not do this and check the content?
     async fn as_leaf(self: Arc<Self>) -> Option<Arc<dyn Leaf>> {
         Some(self)
@@ -88,6 +116,14 @@ impl Entry for FsEntry {
     async fn as_collection_list(self: Arc<Self>) -> Option<Arc<dyn CollectionList>> {
         Some(self)
     }
+
+    async fn as_writable_leaf(self: Arc<Self>) -> Option<Arc<dyn WritableLeaf>> {
+        Some(self)
+    }
+
+    async fn as_writable_collection(self: Arc<Self>) -> Option<Arc<dyn WritableCollection>> {
+        Some(self)
+    }
 }
 
 #[async_trait]
@@ -119,7 +155,9 @@ impl Collection for FsEntry {
 impl CollectionList for FsEntry {
     async fn list(self: Arc<Self>) -> Result<Arc<[Arc<dyn Entry>]>> {
         match self.content().await? {
-            FsEntryContent::CollectionList(entries) => Ok(entries),
+            FsEntryContent::CollectionList(entries) => {
+                Ok(entries.iter().map(|entry| entry.clone() as Arc<dyn Entry>).collect())
+            },
 
             FsEntryContent::Leaf(_) => {
                 bail!("failed to list {this} as it is a file", this = display!(self))
@@ -128,7 +166,191 @@ impl CollectionList for FsEntry {
     }
 }
 
+#[async_trait]
+impl WritableLeaf for FsEntry {
+    async fn write(self: Arc<Self>, content: Bytes) -> Result<()> {
+        write_atomic(&self.path(), &content).await?;
+
+        self.invalidate();
+
+        // The leaf may not have existed before.
+        if let FsEntryLocation::Child { parent, .. } = &self.location {
+            parent.invalidate();
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl WritableCollection for FsEntry {
+    async fn write_leaf(self: Arc<Self>, name: &str, content: Bytes) -> Result<Arc<dyn WritableLeaf>> {
+        let child = self.child(name)?;
+        child.clone().write(content).await?;
+
+        Ok(child)
+    }
+
+    async fn create_collection(self: Arc<Self>, name: &str) -> Result<Arc<dyn WritableCollection>> {
+        let child = self.child(name)?;
+        let path = child.path();
+
+        fs::create_dir_all(&path)
+            .await
+            .with_context(|| format!("failed to create directory '{path}'", path = path.to_string_lossy()))?;
+
+        child.invalidate();
+        self.invalidate();
+        Ok(child)
+    }
+
+    async fn remove(self: Arc<Self>, name: &str) -> Result<()> {
+        let child = self.child(name)?;
+        let path = child.path();
+
+        let metadata = fs::symlink_metadata(&path)
+            .await
+            .with_context(|| format!("failed to get metadata of '{path}'", path = path.to_string_lossy()))?;
+
+        if metadata.is_dir() {
+            fs::remove_dir_all(&path).await
+        } else {
+            fs::remove_file(&path).await
+        }
+        .with_context(|| format!("failed to remove '{path}'", path = path.to_string_lossy()))?;
+
+        child.invalidate();
+        self.invalidate();
+        Ok(())
+    }
+}
+
+/// Writes the content to a temporary file next to the path and renames it to
+/// the path, so readers never observe a partially written file.
+async fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    static COUNT: atomic::AtomicU64 = atomic::AtomicU64::new(0);
+
+    let path_temporary = {
+        let mut name = OsString::from(".");
+        name.push(
+            path.file_name()
+                .with_context(|| format!("failed to get file name of '{path}'", path = path.to_string_lossy()))?,
+        );
+        name.push(format!(
+            ".{id}-{count}.tmp",
+            id = process::id(),
+            count = COUNT.fetch_add(1, atomic::Ordering::Relaxed),
+        ));
+
+        path.with_file_name(name)
+    };
+
+    let written: io::Result<()> = async {
+        let mut file = fs::File::create(&path_temporary).await?;
+
+        if let Ok(metadata) = fs::metadata(path).await {
+            file.set_permissions(metadata.permissions()).await?;
+        }
+
+        file.write_all(content).await?;
+        file.sync_all().await?;
+
+        fs::rename(&path_temporary, path).await
+    }
+    .await;
+
+    if written.is_err() {
+        fs::remove_file(&path_temporary).await.ok();
+    }
+
+    written.with_context(|| format!("failed to write '{path}'", path = path.to_string_lossy()))
+}
+
 impl FsEntry {
+    fn new(location: FsEntryLocation) -> Self {
+        Self {
+            location,
+
+            content: Mutex::new(Arc::new(OnceCell::new())),
+            children: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the child with the given name. The same entry is returned for
+    /// the same name as long as a handle to it is alive.
+    fn child(self: &Arc<Self>, name: &str) -> Result<Arc<Self>> {
+        if name.is_empty() || name == "." || name == ".." || name.contains(path::is_separator) {
+            bail!("invalid entry name '{name}' under {this}", this = display!(self));
+        }
+
+        let mut children = self.children.lock().unwrap();
+
+        if let Some(child) = children.get(name).and_then(Weak::upgrade) {
+            return Ok(child);
+        }
+
+        children.retain(|_, child| child.strong_count() > 0);
+
+        let child = Arc::new(FsEntry::new(FsEntryLocation::Child {
+            parent: self.clone(),
+            name: name.to_owned(),
+        }));
+
+        children.insert(name.to_owned(), Arc::downgrade(&child));
+        Ok(child)
+    }
+
+    /// Returns the child with the given name, if the content of this entry is
+    /// memoized and has the child.
+    fn child_memoized(&self, name: &ffi::OsStr) -> Option<Arc<Self>> {
+        let content = self.content.lock().unwrap().clone();
+
+        let Some(Ok(FsEntryContent::CollectionList(entries))) = content.get() else {
+            return None;
+        };
+
+        entries
+            .iter()
+            .find(|entry| entry.name().is_some_and(|entry_name| entry_name == name))
+            .cloned()
+    }
+
+    /// Invalidates the memoized entry at the given path relative to this entry
+    /// and its parent, as the listing of the parent may have changed too.
+    /// Entries that were not listed through this entry are not affected.
+    #[cfg_attr(not(feature = "watch"), expect(dead_code))]
+    fn invalidate_relative(self: &Arc<Self>, relative: &Path) {
+        let mut names = relative.iter().peekable();
+        let mut entry = Some(self.clone());
+
+        while let Some(current) = entry.take() {
+            let Some(name) = names.next() else {
+                current.invalidate();
+                break;
+            };
+
+            entry = current.child_memoized(name);
+
+            if names.peek().is_none() {
+                current.invalidate();
+            }
+        }
+    }
+
+    /// Invalidates this entry and every memoized entry under it.
+    #[cfg(feature = "watch")]
+    fn invalidate_all(&self) {
+        let content = self.content.lock().unwrap().clone();
+
+        if let Some(Ok(FsEntryContent::CollectionList(entries))) = content.get() {
+            for entry in entries.iter() {
+                entry.invalidate_all();
+            }
+        }
+
+        self.invalidate();
+    }
+
     fn path(&self) -> PathBuf {
         let mut this = self;
         let mut parts = Vec::new();
@@ -149,7 +371,9 @@ impl FsEntry {
     }
 
     async fn content(self: &Arc<Self>) -> Result<FsEntryContent> {
-        self.content.get_or_init(self.content_eager()).await.clone()
+        let content = self.content.lock().unwrap().clone();
+
+        content.get_or_init(self.content_eager()).await.clone()
     }
 
     async fn content_eager(self: &Arc<Self>) -> Result<FsEntryContent> {
@@ -183,7 +407,7 @@ impl FsEntry {
             .await
             .with_context(|| format!("failed to list '{path}'", path = path.to_string_lossy()))?;
 
-        let mut entries = Vec::<Arc<dyn Entry>>::new();
+        let mut entries = Vec::new();
 
         while let Some(entry) = read_dir
             .next_entry()
@@ -192,25 +416,277 @@ impl FsEntry {
         {
             let name = entry.file_name();
 
-            entries.push(Arc::new(FsEntry {
-                location: FsEntryLocation::Child {
-                    parent: self.clone(),
-                    name: name
-                        .to_str()
-                        .with_context(|| {
-                            format!(
-                                "failed to convert name of '{name}' under '{path}' to valid UTF-8",
-                                name = name.to_string_lossy(),
-                                path = path.to_string_lossy(),
-                            )
-                        })?
-                        .to_owned(),
-                },
-
-                content: OnceCell::new(),
-            }))
+            entries.push(self.child(name.to_str().with_context(|| {
+                format!(
+                    "failed to convert name of '{name}' under '{path}' to valid UTF-8",
+                    name = name.to_string_lossy(),
+                    path = path.to_string_lossy(),
+                )
+            })?)?)
         }
 
         Ok(FsEntryContent::CollectionList(entries.into()))
     }
 }
+
+/// Returns the event path relative to whichever root it is under. Paths that
+/// are not under any root are canonicalized and tried again, which resolves
+/// their parent instead if they were removed.
+#[cfg(feature = "watch")]
+fn relative(roots: &[PathBuf], path: &Path) -> Option<PathBuf> {
+    let strip = |path: &Path| {
+        roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+            .map(Path::to_path_buf)
+    };
+
+    if let Some(relative) = strip(path) {
+        return Some(relative);
+    }
+
+    if let Ok(canonical) = path.canonicalize() {
+        return strip(&canonical);
+    }
+
+    let canonical = path.parent()?.canonicalize().ok()?.join(path.file_name()?);
+    strip(&canonical)
+}
+
+/// A watcher that invalidates the memoized content of fs entries when they
+/// change on disk. Watching stops when this is dropped.
+#[cfg(feature = "watch")]
+pub struct FsWatcher {
+    _watcher: notify::RecommendedWatcher,
+    sender: tokio::sync::broadcast::Sender<PathBuf>,
+}
+
+#[cfg(feature = "watch")]
+impl FsWatcher {
+    /// Subscribes to the paths that changed. The paths are sent after the
+    /// entries they belong to are invalidated, so reads done after receiving
+    /// a path observe the new content.
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<PathBuf> {
+        self.sender.subscribe()
+    }
+}
+
+/// Creates an entry from a given fs path, along with an [`FsWatcher`] that
+/// recursively watches the path for changes.
+#[cfg(feature = "watch")]
+pub fn fs_watched(path: PathBuf) -> Result<(Arc<impl WritableLeaf + WritableCollection + CollectionList>, FsWatcher)> {
+    use notify::Watcher as _;
+
+    let entry = Arc::new(FsEntry::new(FsEntryLocation::Root { path: path.clone() }));
+
+    // Event paths are the watched path joined with the names of the changed
+    // entries, but may also be canonical depending on the platform. Match both.
+    let roots = [
+        path::absolute(&path)
+            .with_context(|| format!("failed to make '{path}' absolute", path = path.to_string_lossy()))?,
+        path.canonicalize()
+            .with_context(|| format!("failed to canonicalize '{path}'", path = path.to_string_lossy()))?,
+    ];
+
+    let (sender, _) = tokio::sync::broadcast::channel(64);
+
+    let mut watcher = notify::recommended_watcher({
+        let entry = entry.clone();
+        let sender = sender.clone();
+
+        move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+
+            if event.kind.is_access() {
+                return;
+            }
+
+            for path in event.paths {
+                match relative(&roots, &path) {
+                    Some(relative) => entry.invalidate_relative(&relative),
+
+                    // Better to fetch everything again than to miss a change.
+                    None => entry.invalidate_all(),
+                }
+
+                // Having no subscribers is fine.
+                sender.send(path).ok();
+            }
+        }
+    })
+    .context("failed to create fs watcher")?;
+
+    watcher
+        .watch(&path, notify::RecursiveMode::Recursive)
+        .with_context(|| format!("failed to watch '{path}'", path = path.to_string_lossy()))?;
+
+    Ok((
+        entry,
+        FsWatcher {
+            _watcher: watcher,
+            sender,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    /// Returns an empty directory that is unique to the test.
+    fn directory(test: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("cab-island-{test}-{id}", id = process::id()));
+
+        std::fs::remove_dir_all(&path).ok();
+        std::fs::create_dir_all(&path).unwrap();
+
+        path
+    }
+
+    async fn names(collection: Arc<impl CollectionList>) -> Vec<String> {
+        let mut names: Vec<_> = collection
+            .list()
+            .await
+            .unwrap()
+            .iter()
+            .map(|entry| entry.name().unwrap().to_owned())
+            .collect();
+
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn write_is_atomic() {
+        let path = directory("write_is_atomic");
+        let root = Arc::new(fs(path.clone()));
+
+        let leaf = root.clone().write_leaf("foo", Bytes::from("bar")).await.unwrap();
+        assert_eq!(std::fs::read(path.join("foo")).unwrap(), b"bar");
+
+        leaf.write(Bytes::from("baz")).await.unwrap();
+        assert_eq!(std::fs::read(path.join("foo")).unwrap(), b"baz");
+
+        // No temporary files are left behind.
+        assert_eq!(names(root).await, ["foo"]);
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn read_after_write() {
+        let path = directory("read_after_write");
+        std::fs::write(path.join("foo"), "old").unwrap();
+
+        let root = Arc::new(fs(path.clone()));
+
+        let listed = root.clone().entry("foo").await.unwrap().unwrap();
+        let listed = listed.as_writable_leaf().await.unwrap();
+        assert_eq!(listed.clone().read().await.unwrap(), "old");
+
+        root.clone().write_leaf("foo", Bytes::from("new")).await.unwrap();
+        assert_eq!(listed.clone().read().await.unwrap(), "new");
+
+        listed.clone().write(Bytes::from("newer")).await.unwrap();
+        assert_eq!(listed.read().await.unwrap(), "newer");
+
+        let bar = root.clone().write_leaf("bar", Bytes::from("bar")).await.unwrap();
+        assert_eq!(bar.read().await.unwrap(), "bar");
+        assert_eq!(names(root).await, ["bar", "foo"]);
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn create_collection() {
+        let path = directory("create_collection");
+        let root = Arc::new(fs(path.clone()));
+
+        let collection = root.clone().create_collection("foo").await.unwrap();
+        assert!(path.join("foo").is_dir());
+        assert_eq!(names(root.clone()).await, ["foo"]);
+
+        collection.write_leaf("bar", Bytes::from("baz")).await.unwrap();
+        assert_eq!(std::fs::read(path.join("foo").join("bar")).unwrap(), b"baz");
+
+        // Creating an existing collection keeps its children.
+        root.clone().create_collection("foo").await.unwrap();
+        assert_eq!(std::fs::read(path.join("foo").join("bar")).unwrap(), b"baz");
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn remove() {
+        let path = directory("remove");
+        let root = Arc::new(fs(path.clone()));
+
+        root.clone().write_leaf("foo", Bytes::from("foo")).await.unwrap();
+        root.clone()
+            .create_collection("bar")
+            .await
+            .unwrap()
+            .write_leaf("baz", Bytes::from("baz"))
+            .await
+            .unwrap();
+        assert_eq!(names(root.clone()).await, ["bar", "foo"]);
+
+        root.clone().remove("foo").await.unwrap();
+        root.clone().remove("bar").await.unwrap();
+
+        assert!(!path.join("foo").exists());
+        assert!(!path.join("bar").exists());
+        assert!(names(root.clone()).await.is_empty());
+
+        assert!(root.remove("foo").await.is_err());
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[cfg(all(unix, feature = "watch"))]
+    #[tokio::test]
+    async fn watch_through_symlink() {
+        use std::time::Duration;
+
+        use tokio::sync::broadcast::error::RecvError;
+
+        let path = directory("watch_through_symlink");
+        std::fs::create_dir(path.join("real")).unwrap();
+        std::fs::write(path.join("real").join("foo"), "old").unwrap();
+        std::os::unix::fs::symlink(path.join("real"), path.join("link")).unwrap();
+
+        let (root, watcher) = fs_watched(path.join("link")).unwrap();
+        let mut changes = watcher.subscribe();
+
+        let foo = root
+            .clone()
+            .entry("foo")
+            .await
+            .unwrap()
+            .unwrap()
+            .as_leaf()
+            .await
+            .unwrap();
+        assert_eq!(foo.clone().read().await.unwrap(), "old");
+
+        std::fs::write(path.join("real").join("foo"), "new").unwrap();
+
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while foo.clone().read().await.unwrap() != "new" {
+                // Missed changes are fine, as the content is read again anyway.
+                match changes.recv().await {
+                    Ok(_) | Err(RecvError::Lagged(_)) => {},
+                    Err(RecvError::Closed) => panic!("watcher must not close"),
+                }
+            }
+        })
+        .await
+        .expect("change must be observed");
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -3,8 +3,11 @@
 //! An island is a virtual filesystem root or leaf, also known as an [`Entry`].
 //!
 //! An entry may be a [`Leaf`] or a [`Collection`] and its derivatives, or both.
+//! Entries that can be modified may also be a [`WritableLeaf`] or a
+//! [`WritableCollection`].
 use std::{
     fmt,
+    path::PathBuf,
     sync::Arc,
 };
 
@@ -17,6 +20,11 @@ pub use blob::blob;
 
 mod fs;
 pub use fs::fs;
+#[cfg(feature = "watch")]
+pub use fs::{
+    FsWatcher,
+    fs_watched,
+};
 
 mod stdin;
 pub use stdin::stdin;
@@ -37,6 +45,17 @@ pub trait Entry: fmt::Display + Send + Sync + 'static {
         None
     }
 
+    /// Returns the path of this entry on the local filesystem, if it is backed
+    /// by one.
+    fn local_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Drops the memoized content of this entry, if there is any, so that it
+    /// is fetched again the next time it is accessed. Children that were
+    /// listed before the invalidation are not affected.
+    fn invalidate(&self) {}
+
     /// Tries to use this entry as a [`Leaf`].
     async fn as_leaf(self: Arc<Self>) -> Option<Arc<dyn Leaf>> {
         None
@@ -51,6 +70,16 @@ pub trait Entry: fmt::Display + Send + Sync + 'static {
     async fn as_collection_list(self: Arc<Self>) -> Option<Arc<dyn CollectionList>> {
         None
     }
+
+    /// Tries to use this entry as a [`WritableLeaf`].
+    async fn as_writable_leaf(self: Arc<Self>) -> Option<Arc<dyn WritableLeaf>> {
+        None
+    }
+
+    /// Tries to use this entry as a [`WritableCollection`].
+    async fn as_writable_collection(self: Arc<Self>) -> Option<Arc<dyn WritableCollection>> {
+        None
+    }
 }
 
 /// Converts the given object wrapped in an [`Arc`] that is castable to an
@@ -115,3 +144,28 @@ pub trait CollectionList: Collection {
     /// Lists the children of this collection.
     async fn list(self: Arc<Self>) -> Result<Arc<[Arc<dyn Entry>]>>;
 }
+
+/// A writable leaf. Writing to a leaf replaces its contents completely, and
+/// subsequent reads must observe the written contents.
+#[async_trait]
+pub trait WritableLeaf: Leaf {
+    /// Replaces the contents of this leaf.
+    async fn write(self: Arc<Self>, content: Bytes) -> Result<()>;
+}
+
+/// A writable collection. Writable collections can have children created,
+/// replaced and removed.
+#[async_trait]
+pub trait WritableCollection: Collection {
+    /// Creates a leaf with the given name and content in this collection,
+    /// replacing the existing child with the same name if it is a leaf.
+    async fn write_leaf(self: Arc<Self>, name: &str, content: Bytes) -> Result<Arc<dyn WritableLeaf>>;
+
+    /// Creates an empty collection with the given name in this collection.
+    /// Does nothing but return the existing collection if it already exists.
+    async fn create_collection(self: Arc<Self>, name: &str) -> Result<Arc<dyn WritableCollection>>;
+
+    /// Removes the child with the given name from this collection, along with
+    /// all of its children.
+    async fn remove(self: Arc<Self>, name: &str) -> Result<()>;
+}
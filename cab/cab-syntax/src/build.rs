@@ -0,0 +1,597 @@
+//! Programmatic construction of [`node::Expression`]s.
+//!
+//! The expressions built here are laid out with canonical trivia and are
+//! parenthesized where the binding powers of their operators require it, so
+//! they parse back into the exact same tree without any reports.
+//!
+//! # Example
+//!
+//! ```rs
+//! // { name = "foo", build = @inputs => inputs . bar }
+//! let expression = build::attributes([
+//!     ("name", build::string("foo")?),
+//!     (
+//!         "build",
+//!         build::lambda(
+//!             build::bind("inputs")?,
+//!             build::infix(build::identifier("inputs")?, node::InfixOperator::Select, build::identifier("bar")?),
+//!         ),
+//!     ),
+//! ])?;
+//!
+//! let parse = oracle.build(&expression);
+//! ```
+use std::{
+    borrow::Cow,
+    error,
+    fmt,
+};
+
+use num::Signed as _;
+
+use crate::{
+    Kind::{
+        self,
+        *,
+    },
+    green,
+    node::{
+        InfixOperator,
+        PrefixOperator,
+        SuffixOperator,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Element {
+    Token(Kind, Cow<'static, str>),
+    Node(Tree),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tree {
+    kind: Kind,
+    children: Vec<Element>,
+}
+
+impl Tree {
+    fn new(kind: Kind, children: impl IntoIterator<Item = Element>) -> Self {
+        Self {
+            kind,
+            children: children.into_iter().collect(),
+        }
+    }
+
+    fn write(&self, builder: &mut green::NodeBuilder) {
+        builder.start_node(self.kind);
+
+        for child in &self.children {
+            match child {
+                Element::Token(kind, text) => builder.token(*kind, text),
+                Element::Node(tree) => tree.write(builder),
+            }
+        }
+
+        builder.finish_node();
+    }
+}
+
+fn token(kind: Kind, text: impl Into<Cow<'static, str>>) -> Element {
+    Element::Token(kind, text.into())
+}
+
+fn space() -> Element {
+    token(TOKEN_WHITESPACE, " ")
+}
+
+/// An expression that is being built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expression {
+    tree: Tree,
+
+    /// The kind of the first token.
+    first: Kind,
+    /// The minimum left binding power of the infix operators on the left
+    /// edge. Operators that come before this expression with a right binding
+    /// power above this would take its left edge as their operand.
+    left_power: u16,
+    /// The minimum right binding power of the operators on the right edge.
+    /// Operators that come after this expression with a left binding power
+    /// at or above this would take its right edge as their operand.
+    right_power: u16,
+}
+
+impl Expression {
+    fn atom(tree: Tree) -> Self {
+        let first = first_token(&tree).expect("expressions must not be empty");
+
+        Self {
+            tree,
+            first,
+            left_power: u16::MAX,
+            right_power: u16::MAX,
+        }
+    }
+
+    /// Writes this expression into the builder.
+    pub(crate) fn write(&self, builder: &mut green::NodeBuilder) {
+        self.tree.write(builder);
+    }
+
+    /// Wraps this expression in parentheses.
+    pub fn parenthesize(self) -> Self {
+        Self::atom(Tree::new(
+            NODE_PARENTHESIS,
+            [
+                token(TOKEN_PARENTHESIS_LEFT, "("),
+                Element::Node(self.tree),
+                token(TOKEN_PARENTHESIS_RIGHT, ")"),
+            ],
+        ))
+    }
+
+    fn parenthesize_if(self, condition: impl FnOnce(&Self) -> bool) -> Self {
+        if condition(&self) { self.parenthesize() } else { self }
+    }
+}
+
+fn first_token(tree: &Tree) -> Option<Kind> {
+    tree.children.iter().find_map(|child| {
+        match child {
+            Element::Token(kind, _) => Some(*kind),
+            Element::Node(tree) => first_token(tree),
+        }
+    })
+}
+
+/// Creates a parenthesized expression.
+pub fn parenthesis(expression: Expression) -> Expression {
+    expression.parenthesize()
+}
+
+/// Creates an integer. Negative integers are negation prefix operations.
+pub fn integer(value: impl Into<num::BigInt>) -> Expression {
+    let value = value.into();
+
+    let integer = Expression::atom(Tree::new(NODE_INTEGER, [token(TOKEN_INTEGER, value.abs().to_string())]));
+
+    if value.is_negative() {
+        prefix(PrefixOperator::Negation, integer)
+    } else {
+        integer
+    }
+}
+
+/// Creates a float. Negative floats are negation prefix operations.
+///
+/// Returns [`QuoteError::NonFinite`] if the float is infinite or NaN, as that
+/// cannot be expressed.
+pub fn float(value: f64) -> Result<Expression, QuoteError> {
+    if !value.is_finite() {
+        return Err(QuoteError::NonFinite);
+    }
+
+    let mut text = format!("{value:?}", value = value.abs());
+
+    // Scientific notation is only valid after a fraction.
+    if !text.contains('.') {
+        let exponent = text.find('e').unwrap_or(text.len());
+        text.insert_str(exponent, ".0");
+    }
+
+    let float = Expression::atom(Tree::new(NODE_FLOAT, [token(TOKEN_FLOAT, text)]));
+
+    Ok(if value.is_sign_negative() && value != 0.0 {
+        prefix(PrefixOperator::Negation, float)
+    } else {
+        float
+    })
+}
+
+/// An error that is returned when building an expression with content that
+/// cannot be expressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteError {
+    /// The content starts with `=`, which would be a part of the delimiter.
+    StartsWithEquals,
+    /// The content contains a control character that has no escape.
+    ControlCharacter(char),
+    /// The float is infinite or NaN.
+    NonFinite,
+    /// The island name does not start with an identifier character, or
+    /// contains `>`, `:`, `\` or control characters.
+    InvalidIslandName,
+}
+
+impl fmt::Display for QuoteError {
+    fn fmt(&self, writer: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StartsWithEquals => write!(writer, "content that starts with '=' cannot be delimited"),
+            Self::ControlCharacter(c) => write!(writer, "content cannot contain the control character {c:?}"),
+            Self::NonFinite => write!(writer, "only finite floats can be built"),
+            Self::InvalidIslandName => write!(writer, "island name cannot be expressed"),
+        }
+    }
+}
+
+impl error::Error for QuoteError {}
+
+/// Escapes the given content and returns it delimited by the given quote
+/// with the least amount of `=` that does not end it early.
+fn delimited(content: &str, quote: char) -> Result<(String, String, String), QuoteError> {
+    if content.starts_with('=') {
+        return Err(QuoteError::StartsWithEquals);
+    }
+
+    let mut escaped = String::with_capacity(content.len());
+
+    for c in content.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\\"),
+            '\n' => escaped.push_str(r"\n"),
+            '\t' => escaped.push_str(r"\t"),
+            '\r' => escaped.push_str(r"\r"),
+            '\0' => escaped.push_str(r"\0"),
+            c => escaped.push(c),
+        }
+    }
+
+    let mut equals = String::new();
+    while escaped.contains(&format!("{equals}{quote}")) {
+        equals.push('=');
+    }
+
+    Ok((format!("{quote}{equals}"), escaped, format!("{equals}{quote}")))
+}
+
+fn delimited_tree(node: Kind, (start, end): (Kind, Kind), content: &str, quote: char) -> Result<Tree, QuoteError> {
+    let (start_text, content, end_text) = delimited(content, quote)?;
+
+    let mut children = vec![token(start, start_text)];
+
+    // Empty content is not a token.
+    if !content.is_empty() {
+        children.push(token(TOKEN_CONTENT, content));
+    }
+
+    children.push(token(end, end_text));
+
+    Ok(Tree::new(node, children))
+}
+
+/// Creates a string with the given content. Backslashes and control
+/// characters that have escapes are escaped, and quotes are handled by
+/// adding `=`s to the delimiters.
+///
+/// Returns [`QuoteError::StartsWithEquals`] if the content starts with `=`, as
+/// that cannot be expressed.
+pub fn string(content: &str) -> Result<Expression, QuoteError> {
+    let tree = delimited_tree(NODE_STRING, (TOKEN_STRING_START, TOKEN_STRING_END), content, '"')?;
+
+    Ok(Expression::atom(tree))
+}
+
+fn is_plain_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(|c| {
+        let invalid = c.is_ascii_digit() || c == '-' || c == '\'';
+        !invalid && (c.is_alphanumeric() || c == '_')
+    }) && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '\''))
+        && !matches!(name, "if" | "then" | "else")
+}
+
+/// Creates an identifier. Names that are not valid plain identifiers are
+/// quoted.
+///
+/// Returns a [`QuoteError`] if the name needs to be quoted and starts with `=`
+/// or contains control characters that have no escapes.
+pub fn identifier(name: &str) -> Result<Expression, QuoteError> {
+    if is_plain_identifier(name) {
+        return Ok(Expression::atom(Tree::new(
+            NODE_IDENTIFIER,
+            [token(TOKEN_IDENTIFIER, name.to_owned())],
+        )));
+    }
+
+    if let Some(c) = name
+        .chars()
+        .find(|&c| c.is_control() && !matches!(c, '\n' | '\t' | '\r' | '\0'))
+    {
+        return Err(QuoteError::ControlCharacter(c));
+    }
+
+    let tree = delimited_tree(
+        NODE_IDENTIFIER,
+        (TOKEN_IDENTIFIER_START, TOKEN_IDENTIFIER_END),
+        name,
+        '`',
+    )?;
+
+    Ok(Expression::atom(tree))
+}
+
+/// Creates a bind to an identifier with the given name. Returns a
+/// [`QuoteError`] if the identifier cannot be expressed.
+pub fn bind(name: &str) -> Result<Expression, QuoteError> {
+    Ok(Expression::atom(Tree::new(
+        NODE_BIND,
+        [token(TOKEN_AT, "@"), Element::Node(identifier(name)?.tree)],
+    )))
+}
+
+/// Creates an island with the given name, such as `<foo>`.
+///
+/// Returns [`QuoteError::InvalidIslandName`] if the name does not start with a
+/// valid identifier character or contains `>`, `:`, `\` or control
+/// characters.
+pub fn island(name: &str) -> Result<Expression, QuoteError> {
+    let valid = name.chars().next().is_some_and(|c| {
+        let invalid = c.is_ascii_digit() || c == '-' || c == '\'';
+        !invalid && (c.is_alphanumeric() || c == '_')
+    }) && !name.chars().any(|c| matches!(c, '>' | ':' | '\\') || c.is_control());
+
+    if !valid {
+        return Err(QuoteError::InvalidIslandName);
+    }
+
+    let header = Tree::new(
+        NODE_ISLAND_HEADER,
+        [
+            token(TOKEN_ISLAND_HEADER_START, "<"),
+            token(TOKEN_CONTENT, name.to_owned()),
+            token(TOKEN_ISLAND_HEADER_END, ">"),
+        ],
+    );
+
+    Ok(Expression::atom(Tree::new(NODE_ISLAND, [Element::Node(header)])))
+}
+
+/// Joins the given expressions with the same operator, into a chain that
+/// lists and attributes contain.
+fn same_items(items: impl IntoIterator<Item = Expression>) -> Option<Expression> {
+    let items: Vec<_> = items.into_iter().collect();
+
+    items
+        .into_iter()
+        .rev()
+        .reduce(|right, left| infix(left, InfixOperator::Same, right))
+}
+
+fn delimited_expression(node: Kind, (start, end): (Element, Element), expression: Option<Expression>) -> Expression {
+    let children = match expression {
+        Some(expression) => vec![start, space(), Element::Node(expression.tree), space(), end],
+        None => vec![start, end],
+    };
+
+    Expression::atom(Tree::new(node, children))
+}
+
+/// Creates a list of the given items, such as `[ 1, 2 ]`.
+pub fn list(items: impl IntoIterator<Item = Expression>) -> Expression {
+    delimited_expression(
+        NODE_LIST,
+        (token(TOKEN_BRACKET_LEFT, "["), token(TOKEN_BRACKET_RIGHT, "]")),
+        same_items(items),
+    )
+}
+
+/// Creates attributes that bind the given names to values, such as
+/// `{ foo = 1, bar = 2 }`. Returns a [`QuoteError`] if any of the names
+/// cannot be expressed.
+pub fn attributes<'a>(binds: impl IntoIterator<Item = (&'a str, Expression)>) -> Result<Expression, QuoteError> {
+    let binds = binds
+        .into_iter()
+        .map(|(name, value)| Ok(infix(identifier(name)?, InfixOperator::Equal, value)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(delimited_expression(
+        NODE_ATTRIBUTES,
+        (token(TOKEN_CURLYBRACE_LEFT, "{"), token(TOKEN_CURLYBRACE_RIGHT, "}")),
+        same_items(binds),
+    ))
+}
+
+/// Creates a lambda, such as `@x => x`.
+pub fn lambda(parameter: Expression, body: Expression) -> Expression {
+    infix(parameter, InfixOperator::Lambda, body)
+}
+
+/// Creates an application of the function to the argument, such as `f x`.
+pub fn apply(function: Expression, argument: Expression) -> Expression {
+    infix(function, InfixOperator::ImplicitApply, argument)
+}
+
+fn prefix_token(operator: PrefixOperator) -> (Kind, &'static str) {
+    match operator {
+        PrefixOperator::Swwallation => (TOKEN_PLUS, "+"),
+        PrefixOperator::Negation => (TOKEN_MINUS, "-"),
+        PrefixOperator::Not => (TOKEN_EXCLAMATIONMARK, "!"),
+        PrefixOperator::Try => (TOKEN_QUESTIONMARK, "?"),
+    }
+}
+
+fn infix_token(operator: InfixOperator) -> Option<(Kind, &'static str)> {
+    Some(match operator {
+        InfixOperator::Same => (TOKEN_COMMA, ","),
+        InfixOperator::Sequence => (TOKEN_SEMICOLON, ";"),
+
+        InfixOperator::ImplicitApply => return None,
+        InfixOperator::Apply => (TOKEN_LESS_PIPE, "<|"),
+        InfixOperator::Pipe => (TOKEN_PIPE_MORE, "|>"),
+
+        InfixOperator::Concat => (TOKEN_PLUS_PLUS, "++"),
+        InfixOperator::Construct => (TOKEN_COLON, ":"),
+
+        InfixOperator::Select => (TOKEN_PERIOD, "."),
+        InfixOperator::Update => (TOKEN_SLASH_SLASH, "//"),
+
+        InfixOperator::LessOrEqual => (TOKEN_LESS_EQUAL, "<="),
+        InfixOperator::Less => (TOKEN_LESS, "<"),
+        InfixOperator::MoreOrEqual => (TOKEN_MORE_EQUAL, ">="),
+        InfixOperator::More => (TOKEN_MORE, ">"),
+
+        InfixOperator::Equal => (TOKEN_EQUAL, "="),
+        InfixOperator::NotEqual => (TOKEN_EXCLAMATION_EQUAL, "!="),
+
+        InfixOperator::And => (TOKEN_AMPERSAND_AMPERSAND, "&&"),
+        InfixOperator::Or => (TOKEN_PIPE_PIPE, "||"),
+        InfixOperator::Implication => (TOKEN_MINUS_MORE, "->"),
+
+        InfixOperator::All => (TOKEN_AMPERSAND, "&"),
+        InfixOperator::Any => (TOKEN_PIPE, "|"),
+
+        InfixOperator::Addition => (TOKEN_PLUS, "+"),
+        InfixOperator::Subtraction => (TOKEN_MINUS, "-"),
+        InfixOperator::Multiplication => (TOKEN_ASTERISK, "*"),
+        InfixOperator::Power => (TOKEN_CARET, "^"),
+        InfixOperator::Division => (TOKEN_SLASH, "/"),
+
+        InfixOperator::Lambda => (TOKEN_EQUAL_MORE, "=>"),
+    })
+}
+
+/// Creates a prefix operation, parenthesizing the operand if needed.
+pub fn prefix(operator: PrefixOperator, right: Expression) -> Expression {
+    let ((), power) = operator.binding_power();
+    let (kind, text) = prefix_token(operator);
+
+    let right = right.parenthesize_if(|right| right.left_power < power);
+
+    let mut children = vec![token(kind, text)];
+
+    // Avoid merging with the operator of the operand, such as `+ +x` into `++x`.
+    if PrefixOperator::try_from(right.first).is_ok() {
+        children.push(space());
+    }
+
+    children.push(Element::Node(right.tree));
+
+    Expression {
+        tree: Tree::new(NODE_PREFIX_OPERATION, children),
+        first: kind,
+        left_power: u16::MAX,
+        right_power: power.min(right.right_power),
+    }
+}
+
+/// Creates an infix operation, parenthesizing the operands if needed.
+pub fn infix(left: Expression, operator: InfixOperator, right: Expression) -> Expression {
+    let (left_power, right_power) = operator.binding_power();
+    let operator_token = infix_token(operator);
+
+    let left = left.parenthesize_if(|left| left_power >= left.right_power);
+
+    let right = right.parenthesize_if(|right| {
+        right.left_power < right_power
+            || match operator_token {
+                // The right operand has to be an argument, or it would not be applied.
+                None => !right.first.is_argument(),
+                // Would be a suffix operation otherwise.
+                Some((kind, _)) if SuffixOperator::try_from(kind).is_ok() => !Kind::EXPRESSIONS.contains(right.first),
+                Some(_) => false,
+            }
+    });
+
+    let mut children = vec![Element::Node(left.tree)];
+
+    match operator_token {
+        Some((kind @ (TOKEN_COMMA | TOKEN_SEMICOLON), text)) => children.extend([token(kind, text), space()]),
+        Some((kind, text)) => children.extend([space(), token(kind, text), space()]),
+        None => children.push(space()),
+    }
+
+    children.push(Element::Node(right.tree));
+
+    Expression {
+        tree: Tree::new(NODE_INFIX_OPERATION, children),
+        first: left.first,
+        left_power: left_power.min(left.left_power),
+        right_power: right_power.min(right.right_power),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        oracle,
+        tokenize,
+    };
+
+    #[track_caller]
+    fn assert_round_trips(expression: &Expression, expected: &str) {
+        let oracle = oracle();
+
+        let built = oracle.build(expression);
+        let text = built.node.text().to_string();
+        assert_eq!(text, expected);
+
+        let parse = oracle.parse(tokenize(&text));
+        assert_eq!(format!("{:?}", parse.reports), "[]");
+        assert_eq!(format!("{:#?}", built.node), format!("{:#?}", parse.node));
+    }
+
+    #[test]
+    fn delimiters() {
+        assert_round_trips(&string("foo").unwrap(), r#""foo""#);
+        assert_round_trips(&string(r#"say "hi"="#).unwrap(), r#""=say "hi"=="#);
+        assert_round_trips(&string("a\\(b)\n").unwrap(), r#""a\\(b)\n""#);
+        assert_round_trips(&string("").unwrap(), r#""""#);
+
+        assert_round_trips(&identifier("foo-bar'").unwrap(), "foo-bar'");
+        assert_round_trips(&identifier("if").unwrap(), "`if`");
+        assert_round_trips(&identifier("foo bar`").unwrap(), "`=foo bar`=`");
+
+        assert_eq!(string("=foo"), Err(QuoteError::StartsWithEquals));
+        assert_eq!(identifier("=foo"), Err(QuoteError::StartsWithEquals));
+        assert_eq!(identifier("foo\u{7}"), Err(QuoteError::ControlCharacter('\u{7}')));
+        assert_eq!(attributes([("=foo", integer(1))]), Err(QuoteError::StartsWithEquals));
+        assert_eq!(float(f64::NAN), Err(QuoteError::NonFinite));
+        assert_eq!(island("foo>"), Err(QuoteError::InvalidIslandName));
+        assert_eq!(island("1foo"), Err(QuoteError::InvalidIslandName));
+    }
+
+    #[test]
+    fn parenthesization() {
+        let sum = infix(integer(1), InfixOperator::Addition, integer(2));
+
+        assert_round_trips(
+            &infix(sum.clone(), InfixOperator::Multiplication, sum.clone()),
+            "(1 + 2) * (1 + 2)",
+        );
+        assert_round_trips(
+            &infix(integer(-1), InfixOperator::Addition, float(1e100).unwrap()),
+            "-1 + 1.0e100",
+        );
+        assert_round_trips(&prefix(PrefixOperator::Negation, integer(-1)), "- -1");
+        assert_round_trips(&apply(identifier("f").unwrap(), integer(-1)), "f (-1)");
+        assert_round_trips(
+            &list([sum.clone(), infix(integer(3), InfixOperator::Sequence, integer(4))]),
+            "[ 1 + 2, (3; 4) ]",
+        );
+    }
+
+    #[test]
+    fn attributes_and_lambdas() {
+        assert_round_trips(
+            &attributes([
+                ("name", string("foo").unwrap()),
+                (
+                    "build",
+                    lambda(
+                        bind("inputs").unwrap(),
+                        infix(
+                            identifier("inputs").unwrap(),
+                            InfixOperator::Select,
+                            island("bar").unwrap(),
+                        ),
+                    ),
+                ),
+            ])
+            .unwrap(),
+            r#"{ name = "foo", build = @inputs => inputs . <bar> }"#,
+        );
+        assert_round_trips(&list([]), "[]");
+    }
+}
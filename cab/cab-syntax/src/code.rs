@@ -0,0 +1,234 @@
+//! Stable codes of the reports produced while parsing and validating.
+//!
+//! The documentation of every code is its long-form explanation, which can be
+//! retrieved with [`explain`].
+
+macro_rules! codes {
+    ($(
+        $(#[doc = $doc:literal])*
+        $name:ident = $code:literal;
+    )*) => {
+        $(
+            $(#[doc = $doc])*
+            pub const $name: &str = $code;
+        )*
+
+        /// All codes, in ascending order.
+        pub const ALL: &[&str] = &[$($code),*];
+
+        /// Returns the lines of the long-form explanation of the given code,
+        /// formatted as Markdown.
+        pub fn explain(code: &str) -> Option<impl Iterator<Item = &'static str>> {
+            let lines: &'static [&'static str] = match code {
+                $($code => &[$($doc),*],)*
+                _ => return None,
+            };
+
+            Some(lines.iter().map(|line| line.strip_prefix(' ').unwrap_or(line)))
+        }
+    };
+}
+
+codes! {
+    /// A token or the end of the file was found where it was not expected.
+    ///
+    /// Erroneous code example:
+    ///
+    /// ```cab
+    /// [ 1, 2 )
+    /// ```
+    ///
+    /// The label lists everything that would have been accepted at that point.
+    /// Usually a delimiter is missing or mistyped, or an operator has no
+    /// right-hand side:
+    ///
+    /// ```cab
+    /// [ 1, 2 ]
+    /// ```
+    UNEXPECTED = "E0001";
+
+    /// A parenthesis was opened and closed without an expression inside.
+    ///
+    /// Erroneous code example:
+    ///
+    /// ```cab
+    /// ()
+    /// ```
+    ///
+    /// Parentheses only group an expression, so they must contain one:
+    ///
+    /// ```cab
+    /// (1 + 2)
+    /// ```
+    PARENTHESIS_EMPTY = "E0002";
+
+    /// A parenthesis was opened but never closed.
+    ///
+    /// Erroneous code example:
+    ///
+    /// ```cab
+    /// (1 + 2
+    /// ```
+    ///
+    /// Close it with a `)`:
+    ///
+    /// ```cab
+    /// (1 + 2)
+    /// ```
+    PARENTHESIS_UNCLOSED = "E0003";
+
+    /// The expression inside a list is a sequence. List items are separated
+    /// with `,`, not `;`.
+    ///
+    /// Erroneous code example:
+    ///
+    /// ```cab
+    /// [ 1; 2 ]
+    /// ```
+    ///
+    /// Separate the items with `,`, or parenthesize the sequence if it is meant
+    /// to be a single item:
+    ///
+    /// ```cab
+    /// [ 1, 2 ]
+    /// [ (1; 2) ]
+    /// ```
+    LIST_SEQUENCE = "E0004";
+
+    /// A list was opened but never closed.
+    ///
+    /// Erroneous code example:
+    ///
+    /// ```cab
+    /// [ 1, 2
+    /// ```
+    ///
+    /// Close it with a `]`:
+    ///
+    /// ```cab
+    /// [ 1, 2 ]
+    /// ```
+    LIST_UNCLOSED = "E0005";
+
+    /// Attributes were opened but never closed.
+    ///
+    /// Erroneous code example:
+    ///
+    /// ```cab
+    /// { foo = 1
+    /// ```
+    ///
+    /// Close them with a `}`:
+    ///
+    /// ```cab
+    /// { foo = 1 }
+    /// ```
+    ATTRIBUTES_UNCLOSED = "E0006";
+
+    /// The application operator `<|` and the pipe operator `|>` were mixed
+    /// without parentheses. They associate in opposite directions, so the
+    /// grouping would be ambiguous.
+    ///
+    /// Erroneous code example:
+    ///
+    /// ```cab
+    /// f <| x |> g
+    /// ```
+    ///
+    /// Add parentheses to make the grouping explicit:
+    ///
+    /// ```cab
+    /// f <| (x |> g)
+    /// (f <| x) |> g
+    /// ```
+    APPLY_PIPE_MIXED = "E0007";
+
+    /// An island header contains an invalid escape or a control character.
+    ///
+    /// Erroneous code example:
+    ///
+    /// ```cab
+    /// <foo\q>
+    /// ```
+    ///
+    /// Escapes must be one of `\0`, `\t`, `\n`, `\r`, `` \` ``, `\"`, `\'`,
+    /// `\>` and `\\`, so a backslash must be escaped itself. Control characters
+    /// such as tabs must be escaped too:
+    ///
+    /// ```cab
+    /// <foo\\q>
+    /// <foo\tbar>
+    /// ```
+    ISLAND_INVALID = "E0008";
+
+    /// Something other than an identifier was bound with `@`.
+    ///
+    /// Erroneous code example:
+    ///
+    /// ```cab
+    /// @1
+    /// ```
+    ///
+    /// Only identifiers can be bound:
+    ///
+    /// ```cab
+    /// @foo
+    /// ```
+    BIND_INVALID = "E0009";
+
+    /// A quoted identifier contains an invalid escape or a control character.
+    ///
+    /// Erroneous code example:
+    ///
+    /// ```cab
+    /// `foo\q`
+    /// ```
+    ///
+    /// Escapes must be one of `\0`, `\t`, `\n`, `\r`, `` \` ``, `\"`, `\'`
+    /// and `\\`. Control characters such as newlines must be escaped:
+    ///
+    /// ```cab
+    /// `foo\nbar`
+    /// ```
+    IDENTIFIER_INVALID = "E0010";
+
+    /// A string contains an invalid escape, mixes different kinds of whitespace
+    /// in its indentation, or is multiline without its first and last lines
+    /// being empty.
+    ///
+    /// Erroneous code example:
+    ///
+    /// ```cab
+    /// "foo
+    ///   bar"
+    /// ```
+    ///
+    /// Multiline strings start and end with a newline, which are not part of
+    /// the string. Their indentation is stripped, so it must consist of the
+    /// same kind of whitespace:
+    ///
+    /// ```cab
+    /// "
+    ///   foo
+    ///   bar
+    /// "
+    /// ```
+    STRING_INVALID = "E0011";
+
+    /// A rune is empty, contains more than a single character, contains an
+    /// interpolation or contains a control character.
+    ///
+    /// Erroneous code example:
+    ///
+    /// ```cab
+    /// 'ab'
+    /// ```
+    ///
+    /// A rune is exactly one character or escape:
+    ///
+    /// ```cab
+    /// 'a'
+    /// '\n'
+    /// ```
+    RUNE_INVALID = "E0012";
+}
@@ -0,0 +1,544 @@
+//! Extraction and rendering of documentation comments.
+//!
+//! The documentation of an attribute binding is the comments in its leading
+//! trivia, as attached by [`Attached`], that come after the last blank
+//! line:
+//!
+//! ```txt
+//! {
+//!   # Not documentation, as a blank line follows.
+//!
+//!   # Adds two numbers.
+//!   add = @x => @y => x + y,
+//! }
+//! ```
+//!
+//! Bindings in attributes that are the root expression or the body of a lambda
+//! bound to an attribute are collected, with their attribute paths.
+use std::io;
+
+use cab_why::{
+    IntoSpan as _,
+    Span,
+};
+
+use crate::{
+    highlight,
+    node::{
+        Attached,
+        ExpressionRef,
+        InfixOperator,
+        TriviaRef,
+    },
+    token,
+};
+
+/// A documented expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    /// The attribute path of the binding, such as `foo.bar` for `bar` in
+    /// `{ foo = { bar = ... } }`. Empty for the root expression.
+    pub path: Vec<String>,
+    /// The patterns of the lambdas the expression is, in the order they are
+    /// applied in.
+    pub patterns: Vec<String>,
+    /// The text of the documentation comments, without their delimiters.
+    pub doc: String,
+    /// The span of the binding, or of the root expression.
+    pub span: Span,
+}
+
+impl Item {
+    /// Returns the signature of this item, such as `add = @x => @y => ...`.
+    pub fn signature(&self) -> String {
+        let mut signature = String::new();
+
+        if !self.path.is_empty() {
+            signature.push_str(&self.path.join("."));
+            signature.push_str(" = ");
+        }
+
+        for pattern in &self.patterns {
+            signature.push_str(pattern);
+            signature.push_str(" => ");
+        }
+
+        signature.push_str("...");
+        signature
+    }
+}
+
+/// Collects the items of the given root expression, in source order.
+///
+/// Undocumented bindings are collected too, so every attribute path is
+/// listed. The root expression is only collected if it is documented or is a
+/// lambda.
+pub fn items(expression: ExpressionRef<'_>) -> Vec<Item> {
+    let mut items = Vec::new();
+
+    let (patterns, body) = lambda(expression);
+    let doc = doc(&expression);
+
+    if !doc.is_empty() || !patterns.is_empty() {
+        items.push(Item {
+            path: Vec::new(),
+            patterns,
+            doc,
+            span: expression.span(),
+        });
+    }
+
+    collect(body, &mut Vec::new(), &mut items);
+    items
+}
+
+fn collect(expression: ExpressionRef<'_>, path: &mut Vec<String>, items: &mut Vec<Item>) {
+    let expression = match expression {
+        ExpressionRef::Parenthesis(parenthesis) => {
+            if let Some(expression) = parenthesis.expression() {
+                collect(expression, path, items);
+            }
+
+            return;
+        },
+
+        ExpressionRef::Attributes(attributes) => {
+            let Some(expression) = attributes.expression() else {
+                return;
+            };
+
+            expression
+        },
+
+        _ => return,
+    };
+
+    for binding in expression.same_items() {
+        let ExpressionRef::InfixOperation(binding) = binding else {
+            continue;
+        };
+
+        if binding.operator() != InfixOperator::Equal {
+            continue;
+        }
+
+        let path_len = path.len();
+        push_key(binding.left(), path);
+
+        let (patterns, body) = lambda(binding.right());
+
+        items.push(Item {
+            path: path.clone(),
+            patterns,
+            doc: doc(binding),
+            span: binding.span(),
+        });
+
+        collect(body, path, items);
+        path.truncate(path_len);
+    }
+}
+
+/// Pushes the parts of the given attribute key, splitting selections.
+fn push_key(key: ExpressionRef<'_>, to: &mut Vec<String>) {
+    match key {
+        ExpressionRef::InfixOperation(operation) if operation.operator() == InfixOperator::Select => {
+            push_key(operation.left(), to);
+            push_key(operation.right(), to);
+        },
+
+        key => to.push(key.text().to_string().trim().to_owned()),
+    }
+}
+
+/// Returns the patterns of the lambdas the expression is and the body of the
+/// innermost one.
+fn lambda(mut expression: ExpressionRef<'_>) -> (Vec<String>, ExpressionRef<'_>) {
+    let mut patterns = Vec::new();
+
+    while let ExpressionRef::InfixOperation(operation) = expression
+        && operation.operator() == InfixOperator::Lambda
+    {
+        patterns.push(operation.left().text().to_string().trim().to_owned());
+        expression = operation.right();
+    }
+
+    (patterns, expression)
+}
+
+/// Returns the documentation of the given node. Comments before a blank line
+/// in the leading trivia are not documentation.
+fn doc(node: &impl Attached) -> String {
+    let trivia = node.leading_trivia();
+
+    let start = trivia
+        .iter()
+        .rposition(|item| matches!(item, TriviaRef::Whitespace(_)) && item.newline_count() >= 2)
+        .map_or(0, |index| index + 1);
+
+    trivia[start..]
+        .iter()
+        .filter_map(|item| {
+            match item {
+                TriviaRef::Comment(comment) => Some(comment_text(comment)),
+                TriviaRef::Whitespace(_) => None,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the text of the comment without its delimiters. The lines of
+/// multiline comments after the first are dedented.
+fn comment_text(comment: &token::Comment) -> String {
+    let start = comment.start_delimiter();
+    let text = &comment.text()[start.len()..];
+
+    if !comment.is_multiline() {
+        return text.strip_prefix(' ').unwrap_or(text).trim_end().to_owned();
+    }
+
+    let end = format!("{equals}#", equals = &start[1..]);
+    let text = text.strip_suffix(&*end).unwrap_or(text);
+
+    let mut lines = text.lines();
+    let first = lines.next().unwrap_or_default().trim();
+    let rest: Vec<_> = lines.collect();
+
+    let indent = rest
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.chars().take_while(|c| c.is_whitespace()).count())
+        .min()
+        .unwrap_or(0);
+
+    let mut dedented = String::from(first);
+
+    for line in rest {
+        let line: String = line.chars().skip(indent).collect();
+
+        dedented.push('\n');
+        dedented.push_str(line.trim_end());
+    }
+
+    dedented.trim_matches('\n').to_owned()
+}
+
+/// Returns the given code as Markdown inline code.
+fn markdown_code(code: &str) -> String {
+    if code.contains('`') {
+        format!("`` {code} ``")
+    } else {
+        format!("`{code}`")
+    }
+}
+
+/// Returns the given text with the Markdown syntax in it escaped, so it is
+/// displayed as is.
+fn markdown_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '!' | '|') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Returns the given line with every `<` outside of code spans escaped, so it
+/// does not start inline HTML. Backticks that do not open a code span are
+/// literal, like in Markdown.
+fn markdown_line(line: &str) -> String {
+    let mut escaped = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(start) = rest.find(['<', '`']) {
+        escaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix('<') {
+            escaped.push_str(r"\<");
+            rest = after;
+            continue;
+        }
+
+        let ticks = rest.len() - rest.trim_start_matches('`').len();
+
+        // A code span is closed by a backtick run of the same length.
+        let mut offset = ticks;
+        let end = loop {
+            let Some(start) = rest[offset..].find('`') else {
+                break ticks;
+            };
+
+            let run_start = offset + start;
+            let run = rest[run_start..].len() - rest[run_start..].trim_start_matches('`').len();
+
+            if run == ticks {
+                break run_start + run;
+            }
+
+            offset = run_start + run;
+        };
+
+        escaped.push_str(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    escaped.push_str(rest);
+    escaped
+}
+
+/// Returns the given documentation as Markdown. Lines outside of code blocks
+/// that start with `#` are escaped, so they do not become headings, and so is
+/// inline HTML outside of code spans.
+fn markdown_doc(doc: &str) -> String {
+    let mut fenced = false;
+
+    doc.lines()
+        .map(|line| {
+            let content = line.trim_start();
+            let indent = &line[..line.len() - content.len()];
+
+            if content.starts_with("```") {
+                fenced = !fenced;
+            }
+
+            if fenced || content.starts_with("```") {
+                line.to_owned()
+            } else if content.starts_with('#') {
+                format!(r"{indent}\{content}", content = markdown_line(content))
+            } else {
+                markdown_line(line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes the items as a Markdown page with the given title, which is escaped.
+pub fn markdown(writer: &mut impl io::Write, title: &str, items: &[Item]) -> io::Result<()> {
+    let mut blocks = vec![format!("# {title}", title = markdown_text(title))];
+
+    for item in items {
+        if !item.path.is_empty() {
+            blocks.push(format!("## {path}", path = markdown_code(&item.path.join("."))));
+        }
+
+        if !item.patterns.is_empty() {
+            blocks.push(format!("```cab\n{signature}\n```", signature = item.signature()));
+        }
+
+        if !item.doc.is_empty() {
+            blocks.push(markdown_doc(&item.doc));
+        }
+    }
+
+    writeln!(writer, "{blocks}", blocks = blocks.join("\n\n"))
+}
+
+const CSS: &str = "\
+body { max-width: 60em; margin: 0 auto; padding: 1em; font-family: sans-serif; line-height: 1.5; }
+section { border-top: 1px solid #D0D7DE; }
+pre { padding: 0.5em 1em; overflow-x: auto; background: #F6F8FA; }
+
+@media (prefers-color-scheme: dark) {
+  body { color: #C9D1D9; background: #0D1117; }
+  section { border-color: #30363D; }
+  pre { background: #161B22; }
+}
+";
+
+/// Writes the items as a standalone HTML page with the given title. Every
+/// attribute is a `<section>` with its path as its id.
+pub fn html(writer: &mut impl io::Write, title: &str, items: &[Item]) -> io::Result<()> {
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, r#"<html lang="en">"#)?;
+    writeln!(writer, "<head>")?;
+    writeln!(writer, r#"<meta charset="utf-8">"#)?;
+
+    write!(writer, "<title>")?;
+    highlight::write_escaped(writer, title)?;
+    writeln!(writer, "</title>")?;
+
+    writeln!(writer, "<style>\n{CSS}</style>")?;
+    writeln!(writer, "</head>")?;
+    writeln!(writer, "<body>")?;
+
+    write!(writer, "<h1>")?;
+    highlight::write_escaped(writer, title)?;
+    writeln!(writer, "</h1>")?;
+
+    for item in items {
+        let path = item.path.join(".");
+
+        if !path.is_empty() {
+            write!(writer, r#"<section id=""#)?;
+            highlight::write_escaped(writer, &path)?;
+            write!(writer, r#""><h2><code>"#)?;
+            highlight::write_escaped(writer, &path)?;
+            writeln!(writer, "</code></h2>")?;
+        }
+
+        if !item.patterns.is_empty() {
+            write!(writer, "<pre><code>")?;
+            highlight::write_escaped(writer, &item.signature())?;
+            writeln!(writer, "</code></pre>")?;
+        }
+
+        for paragraph in item.doc.split("\n\n").filter(|paragraph| !paragraph.trim().is_empty()) {
+            write!(writer, "<p>")?;
+            highlight::write_escaped(writer, paragraph.trim())?;
+            writeln!(writer, "</p>")?;
+        }
+
+        if !path.is_empty() {
+            writeln!(writer, "</section>")?;
+        }
+    }
+
+    writeln!(writer, "</body>")?;
+    writeln!(writer, "</html>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        oracle,
+        tokenize,
+    };
+
+    #[test]
+    fn items() {
+        let source = r#"# The library.
+@inputs => {
+  # Not documentation.
+
+  # Adds two numbers.
+  add = @x => @y => x + y,
+
+  #= Nested
+       attributes.
+  =#
+  nested = { `quoted key` = 1 },
+
+  a.b = 2,
+}"#;
+
+        let parse = oracle().parse(tokenize(source));
+
+        let items: Vec<_> = super::items(parse.expression.as_ref())
+            .into_iter()
+            .map(|item| (item.path.join("."), item.signature(), item.doc))
+            .collect();
+
+        assert_eq!(
+            items,
+            [
+                ("", "@inputs => ...", "The library."),
+                ("add", "add = @x => @y => ...", "Adds two numbers."),
+                ("nested", "nested = ...", "Nested\nattributes."),
+                ("nested.`quoted key`", "nested.`quoted key` = ...", ""),
+                ("a.b", "a.b = ...", ""),
+            ]
+            .map(|(path, signature, doc)| (path.to_owned(), signature.to_owned(), doc.to_owned())),
+        );
+    }
+
+    fn rendered_items() -> Vec<Item> {
+        vec![
+            Item {
+                path: Vec::new(),
+                patterns: vec!["@inputs".to_owned()],
+                doc: "The library.".to_owned(),
+                span: Span::empty(0u32),
+            },
+            Item {
+                path: vec!["add".to_owned()],
+                patterns: vec!["@x".to_owned(), "@y".to_owned()],
+                doc: "Adds <two> numbers.\n# Not a heading.\n\n```cab\n# A comment.\n```".to_owned(),
+                span: Span::empty(0u32),
+            },
+            Item {
+                path: vec!["a`b".to_owned()],
+                patterns: Vec::new(),
+                doc: String::new(),
+                span: Span::empty(0u32),
+            },
+        ]
+    }
+
+    #[test]
+    fn markdown() {
+        let mut page = Vec::new();
+        super::markdown(&mut page, "lib.cab", &rendered_items()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(page).unwrap(),
+            r"# lib.cab
+
+```cab
+@inputs => ...
+```
+
+The library.
+
+## `add`
+
+```cab
+add = @x => @y => ...
+```
+
+Adds \<two> numbers.
+\# Not a heading.
+
+```cab
+# A comment.
+```
+
+## `` a`b ``
+",
+        );
+    }
+
+    #[test]
+    fn markdown_escapes() {
+        let items = [Item {
+            path: Vec::new(),
+            patterns: Vec::new(),
+            doc: "# Uses *emphasis* and <tag>, but not `<tag>` or ``a`<b``.\nA stray ` and <tag>.".to_owned(),
+            span: Span::empty(0u32),
+        }];
+
+        let mut page = Vec::new();
+        super::markdown(&mut page, "#1 *lib* <tag>", &items).unwrap();
+
+        assert_eq!(
+            String::from_utf8(page).unwrap(),
+            r"# \#1 \*lib\* \<tag\>
+
+\# Uses *emphasis* and \<tag>, but not `<tag>` or ``a`<b``.
+A stray ` and \<tag>.
+",
+        );
+    }
+
+    #[test]
+    fn html() {
+        let mut page = Vec::new();
+        super::html(&mut page, "<lib>", &rendered_items()).unwrap();
+
+        let page = String::from_utf8(page).unwrap();
+
+        assert!(page.contains("<title>&lt;lib&gt;</title>"));
+        assert!(page.contains("<pre><code>@inputs =&gt; ...</code></pre>\n<p>The library.</p>\n"));
+        assert!(page.contains(r#"<section id="add"><h2><code>add</code></h2>"#));
+        assert!(page.contains("<p>Adds &lt;two&gt; numbers.\n# Not a heading.</p>\n"));
+        assert!(page.contains("<h2><code>a`b</code></h2>\n</section>\n</body>"));
+    }
+}
@@ -4,6 +4,13 @@ use std::{
     io,
 };
 
+#[cfg(feature = "json")]
+use cab_why::Span;
+#[cfg(feature = "json")]
+use serde_json::{
+    Value,
+    json,
+};
 use yansi::Paint as _;
 
 use crate::{
@@ -12,6 +19,7 @@ use crate::{
         self,
         Parted as _,
     },
+    red,
 };
 
 /// Formats the given node with parentheses to disambiguate.
@@ -19,6 +27,43 @@ pub fn parenthesize(writer: &mut impl io::Write, expression: node::ExpressionRef
     Formatter::new(writer).parenthesize(expression)
 }
 
+/// Serializes the given node and everything inside it to JSON.
+///
+/// Every node and token is an object with its `kind` name and the `start` and
+/// `end` byte offsets of its text. Nodes also have their `children`, while
+/// tokens have their `text`.
+#[cfg(feature = "json")]
+pub fn json(node: &red::Node) -> Value {
+    let span = Span::from(node.text_range());
+
+    let children: Vec<_> = node
+        .children_with_tokens()
+        .map(|child| {
+            match child {
+                red::ElementRef::Node(node) => json(node),
+
+                red::ElementRef::Token(token) => {
+                    let span = Span::from(token.text_range());
+
+                    json!({
+                        "kind": format!("{kind:?}", kind = token.kind()),
+                        "start": *span.start,
+                        "end": *span.end,
+                        "text": token.text(),
+                    })
+                },
+            }
+        })
+        .collect();
+
+    json!({
+        "kind": format!("{kind:?}", kind = node.kind()),
+        "start": *span.start,
+        "end": *span.end,
+        "children": children,
+    })
+}
+
 #[derive(Debug)]
 struct Formatter<'write, W: io::Write> {
     inner: &'write mut W,
@@ -0,0 +1,325 @@
+//! Syntax highlighting of parsed sources.
+//!
+//! Tokens are classified into [`Category`]s by their kinds and the nodes they
+//! are in, and the categories are then rendered with a [`Theme`] to ANSI
+//! escapes or with CSS classes to HTML.
+use std::io;
+
+use yansi::Paint as _;
+
+use crate::{
+    Kind::*,
+    red,
+};
+
+/// The semantic category of a highlighted token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    /// The keywords of if expressions.
+    Keyword,
+    /// The delimiters and content of strings, runes and paths.
+    String,
+    /// The delimiters of interpolations.
+    Interpolation,
+    /// Integers and floats.
+    Number,
+    /// Single and multiline comments.
+    Comment,
+    /// The delimiters and content of island headers.
+    Island,
+    /// The tokens of prefix, infix and suffix operators.
+    Operator,
+    /// The `@` of binds and the identifiers bound by them.
+    Bind,
+}
+
+impl Category {
+    /// Returns the name of this category, which is also its CSS class without
+    /// the `cab-` prefix.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::String => "string",
+            Self::Interpolation => "interpolation",
+            Self::Number => "number",
+            Self::Comment => "comment",
+            Self::Island => "island",
+            Self::Operator => "operator",
+            Self::Bind => "bind",
+        }
+    }
+}
+
+/// Returns the category of the given token, or [`None`] if it is not
+/// highlighted, such as whitespace, plain identifiers and brackets.
+pub fn classify(token: &red::Token) -> Option<Category> {
+    let parent = token.parent();
+    let in_operation = matches!(
+        parent.kind(),
+        NODE_PREFIX_OPERATION | NODE_INFIX_OPERATION | NODE_SUFFIX_OPERATION
+    );
+
+    Some(match token.kind() {
+        TOKEN_COMMENT => Category::Comment,
+
+        TOKEN_KEYWORD_IF | TOKEN_KEYWORD_THEN | TOKEN_KEYWORD_ELSE => Category::Keyword,
+
+        TOKEN_INTEGER | TOKEN_FLOAT => Category::Number,
+
+        TOKEN_INTERPOLATION_START | TOKEN_INTERPOLATION_END => Category::Interpolation,
+
+        TOKEN_AT => Category::Bind,
+
+        TOKEN_IDENTIFIER | TOKEN_IDENTIFIER_START | TOKEN_IDENTIFIER_END | TOKEN_CONTENT
+            if parent.kind() == NODE_IDENTIFIER
+                && parent
+                    .parent()
+                    .is_some_and(|grandparent| grandparent.kind() == NODE_BIND) =>
+        {
+            Category::Bind
+        },
+
+        TOKEN_STRING_START | TOKEN_STRING_END | TOKEN_RUNE_START | TOKEN_RUNE_END => Category::String,
+
+        TOKEN_ISLAND_HEADER_START | TOKEN_ISLAND_HEADER_END => Category::Island,
+
+        TOKEN_CONTENT => {
+            match parent.kind() {
+                NODE_STRING | NODE_RUNE | NODE_PATH => Category::String,
+                NODE_ISLAND_HEADER => Category::Island,
+                _ => return None,
+            }
+        },
+
+        kind if in_operation && !kind.is_trivia() && !kind.is_error() => Category::Operator,
+
+        _ => return None,
+    })
+}
+
+/// Returns every token in the given node in order, with its category.
+pub fn highlight(node: &red::Node) -> impl Iterator<Item = (&red::Token, Option<Category>)> {
+    node.descendants_with_tokens()
+        .filter_map(red::ElementRef::into_token)
+        .map(|token| (token, classify(token)))
+}
+
+/// The styles of the [`Category`]s in ANSI output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub keyword: yansi::Style,
+    pub string: yansi::Style,
+    pub interpolation: yansi::Style,
+    pub number: yansi::Style,
+    pub comment: yansi::Style,
+    pub island: yansi::Style,
+    pub operator: yansi::Style,
+    pub bind: yansi::Style,
+}
+
+impl Theme {
+    /// The default theme, for dark terminal backgrounds.
+    pub const DARK: Self = Self {
+        keyword: yansi::Style::new().red().bold(),
+        string: yansi::Style::new().green(),
+        interpolation: yansi::Style::new().magenta(),
+        number: yansi::Style::new().blue().bold(),
+        comment: yansi::Style::new().bright_black().italic(),
+        island: yansi::Style::new().cyan(),
+        operator: yansi::Style::new().yellow(),
+        bind: yansi::Style::new().bright_cyan().bold(),
+    };
+    /// A theme for light terminal backgrounds, avoiding yellow and cyan.
+    pub const LIGHT: Self = Self {
+        keyword: yansi::Style::new().red().bold(),
+        string: yansi::Style::new().green(),
+        interpolation: yansi::Style::new().magenta(),
+        number: yansi::Style::new().blue().bold(),
+        comment: yansi::Style::new().fixed(244).italic(),
+        island: yansi::Style::new().fixed(30),
+        operator: yansi::Style::new().fixed(130),
+        bind: yansi::Style::new().fixed(30).bold(),
+    };
+    /// A theme without colors. Keywords and binds are bold instead.
+    pub const MONOCHROME: Self = Self {
+        keyword: yansi::Style::new().bold(),
+        string: yansi::Style::new(),
+        interpolation: yansi::Style::new(),
+        number: yansi::Style::new(),
+        comment: yansi::Style::new().italic(),
+        island: yansi::Style::new(),
+        operator: yansi::Style::new(),
+        bind: yansi::Style::new().bold(),
+    };
+
+    /// Returns the style of the given category.
+    pub fn style(&self, category: Category) -> yansi::Style {
+        match category {
+            Category::Keyword => self.keyword,
+            Category::String => self.string,
+            Category::Interpolation => self.interpolation,
+            Category::Number => self.number,
+            Category::Comment => self.comment,
+            Category::Island => self.island,
+            Category::Operator => self.operator,
+            Category::Bind => self.bind,
+        }
+    }
+}
+
+/// Writes the source of the given node, painting tokens with the styles of
+/// their categories.
+pub fn ansi(writer: &mut impl io::Write, node: &red::Node, theme: &Theme) -> io::Result<()> {
+    for (token, category) in highlight(node) {
+        match category {
+            Some(category) => write!(writer, "{text}", text = token.text().paint(theme.style(category)))?,
+            None => write!(writer, "{text}", text = token.text())?,
+        }
+    }
+
+    Ok(())
+}
+
+/// The default stylesheet of [`html`] output, for light and dark color
+/// schemes.
+pub const CSS: &str = "\
+.cab { color: #24292F; background: #F6F8FA; }
+.cab-keyword { color: #CF222E; font-weight: bold; }
+.cab-string { color: #0A3069; }
+.cab-interpolation { color: #8250DF; }
+.cab-number { color: #0550AE; font-weight: bold; }
+.cab-comment { color: #6E7781; font-style: italic; }
+.cab-island { color: #116329; }
+.cab-operator { color: #953800; }
+.cab-bind { color: #116329; font-weight: bold; }
+
+@media (prefers-color-scheme: dark) {
+  .cab { color: #C9D1D9; background: #161B22; }
+  .cab-keyword { color: #FF7B72; }
+  .cab-string { color: #A5D6FF; }
+  .cab-interpolation { color: #D2A8FF; }
+  .cab-number { color: #79C0FF; }
+  .cab-comment { color: #8B949E; }
+  .cab-island { color: #7EE787; }
+  .cab-operator { color: #FFA657; }
+  .cab-bind { color: #7EE787; }
+}
+";
+
+pub(crate) fn write_escaped(writer: &mut impl io::Write, text: &str) -> io::Result<()> {
+    let mut rest = text;
+
+    while let Some(index) = rest.find(['&', '<', '>', '"']) {
+        let escaped = match rest.as_bytes()[index] {
+            b'&' => "&amp;",
+            b'<' => "&lt;",
+            b'>' => "&gt;",
+            _ => "&quot;",
+        };
+
+        write!(writer, "{unescaped}{escaped}", unescaped = &rest[..index])?;
+        rest = &rest[index + 1..];
+    }
+
+    write!(writer, "{rest}")
+}
+
+/// Writes the source of the given node as a `<pre class="cab">` element, with
+/// highlighted tokens wrapped in `<span>`s with the `cab-` prefixed names of
+/// their categories as classes.
+pub fn html(writer: &mut impl io::Write, node: &red::Node) -> io::Result<()> {
+    write!(writer, r#"<pre class="cab"><code>"#)?;
+
+    for (token, category) in highlight(node) {
+        match category {
+            Some(category) => {
+                write!(writer, r#"<span class="cab-{name}">"#, name = category.name())?;
+                write_escaped(writer, token.text())?;
+                write!(writer, "</span>")?;
+            },
+
+            None => write_escaped(writer, token.text())?,
+        }
+    }
+
+    writeln!(writer, "</code></pre>")
+}
+
+/// Writes a standalone HTML page with the given title, containing the
+/// [`html`] output of the given node and the default [`CSS`].
+pub fn html_page(writer: &mut impl io::Write, node: &red::Node, title: &str) -> io::Result<()> {
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, r#"<html lang="en">"#)?;
+    writeln!(writer, "<head>")?;
+    writeln!(writer, r#"<meta charset="utf-8">"#)?;
+
+    write!(writer, "<title>")?;
+    write_escaped(writer, title)?;
+    writeln!(writer, "</title>")?;
+
+    writeln!(writer, "<style>\n{CSS}</style>")?;
+    writeln!(writer, "</head>")?;
+    writeln!(writer, "<body>")?;
+
+    html(writer, node)?;
+
+    writeln!(writer, "</body>")?;
+    writeln!(writer, "</html>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        oracle,
+        tokenize,
+    };
+
+    #[track_caller]
+    fn assert_highlights(source: &str, expected: &[(&str, Category)]) {
+        let parse = oracle().parse(tokenize(source));
+
+        let highlighted: Vec<_> = highlight(&parse.node)
+            .filter_map(|(token, category)| Some((token.text(), category?)))
+            .collect();
+
+        assert_eq!(highlighted, expected);
+    }
+
+    #[test]
+    fn categories() {
+        assert_highlights(
+            r#"@x => "a\(x)" + 1 # no"#,
+            &[
+                ("@", Category::Bind),
+                ("x", Category::Bind),
+                ("=>", Category::Operator),
+                ("\"", Category::String),
+                ("a", Category::String),
+                (r"\(", Category::Interpolation),
+                (")", Category::Interpolation),
+                ("\"", Category::String),
+                ("+", Category::Operator),
+                ("1", Category::Number),
+                ("# no", Category::Comment),
+            ],
+        );
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        let parse = oracle().parse(tokenize(r#""<&>""#));
+
+        let mut html = Vec::new();
+        super::html(&mut html, &parse.node).unwrap();
+
+        assert_eq!(
+            String::from_utf8(html).unwrap(),
+            concat!(
+                r#"<pre class="cab"><code><span class="cab-string">"</span>"#,
+                r#"<span class="cab-string">&lt;&amp;&gt;</span><span class="cab-string">"</span></code></pre>"#,
+                "\n",
+            ),
+        );
+    }
+}
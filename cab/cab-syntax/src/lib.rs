@@ -2,8 +2,12 @@
 
 #![feature(assert_matches, gen_blocks, if_let_guard, let_chains, trait_alias)]
 
+pub mod build;
+pub mod code;
 mod color;
+pub mod doc;
 pub mod format;
+pub mod highlight;
 
 use std::ops;
 
@@ -19,15 +23,22 @@ pub use self::{
         Parse,
         oracle,
     },
-    tokenizer::tokenize,
+    tokenizer::{
+        tokenize,
+        tokenize_spanned,
+    },
 };
 
 pub mod node;
 mod noder;
 
+pub mod query;
+
 pub mod token;
 mod tokenizer;
 
+pub mod visit;
+
 #[allow(dead_code)]
 mod red {
     use super::*;
@@ -7,9 +7,11 @@ use std::{
 };
 
 use cab_why::{
+    Applicability,
     IntoSpan,
     Report,
     Span,
+    Suggestion,
 };
 use paste::paste;
 
@@ -18,11 +20,16 @@ use crate::{
         self,
         *,
     },
+    code,
     red,
     token::{
         self,
         ContentPart,
     },
+    visit::{
+        self,
+        Visitor,
+    },
 };
 
 macro_rules! reffed {
@@ -307,24 +314,9 @@ node! {
 }
 
 impl<'a> ExpressionRef<'a> {
+    /// Validates this expression and every expression inside it.
     pub fn validate(self, to: &mut Vec<Report>) {
-        match self {
-            Self::Parenthesis(parenthesis) => parenthesis.validate(to),
-            Self::List(list) => list.validate(to),
-            Self::Attributes(attributes) => attributes.validate(to),
-            Self::PrefixOperation(operation) => operation.validate(to),
-            Self::InfixOperation(operation) => operation.validate(to),
-            Self::SuffixOperation(operation) => operation.validate(to),
-            Self::Path(path) => path.validate(to),
-            Self::Bind(bind) => bind.validate(to),
-            Self::Identifier(identifier) => identifier.validate(to),
-            Self::SString(string) => string.validate(to),
-            Self::Rune(rune) => rune.validate(to),
-            Self::Island(island) => island.validate(to),
-            Self::If(if_else) => if_else.validate(to),
-
-            Self::Error(_) | Self::Integer(_) | Self::Float(_) => {},
-        }
+        Validator { to }.visit_expression(self);
     }
 
     /// Iterates over all subexpressions delimited with the same operator.
@@ -350,6 +342,198 @@ impl<'a> ExpressionRef<'a> {
     }
 }
 
+/// Runs the validation of every node after the nodes inside it, except for
+/// the inner expression of lists, which is validated before the items.
+///
+/// The expressions inside attributes are not validated.
+struct Validator<'to> {
+    to: &'to mut Vec<Report>,
+}
+
+impl<'a> Visitor<'a> for Validator<'_> {
+    fn visit_parenthesis(&mut self, parenthesis: &'a Parenthesis) {
+        visit::walk_parenthesis(self, parenthesis);
+        parenthesis.validate(self.to);
+    }
+
+    fn visit_list(&mut self, list: &'a List) {
+        list.validate_expression(self.to);
+        visit::walk_list(self, list);
+        list.validate(self.to);
+    }
+
+    fn visit_attributes(&mut self, attributes: &'a Attributes) {
+        attributes.validate(self.to);
+    }
+
+    fn visit_infix_operation(&mut self, operation: &'a InfixOperation) {
+        visit::walk_infix_operation(self, operation);
+        operation.validate(self.to);
+    }
+
+    fn visit_island(&mut self, island: &'a Island) {
+        visit::walk_island(self, island);
+        island.validate(self.to);
+    }
+
+    fn visit_bind(&mut self, bind: &'a Bind) {
+        // Binds to anything other than identifiers are already invalid.
+        if let ExpressionRef::Identifier(identifier) = bind.identifier() {
+            self.visit_identifier(identifier);
+        }
+
+        bind.validate(self.to);
+    }
+
+    fn visit_identifier(&mut self, identifier: &'a Identifier) {
+        visit::walk_identifier(self, identifier);
+        identifier.validate(self.to);
+    }
+
+    fn visit_string(&mut self, string: &'a SString) {
+        visit::walk_string(self, string);
+        string.validate(self.to);
+    }
+
+    fn visit_rune(&mut self, rune: &'a Rune) {
+        // Interpolation is not allowed in runes, so it is not walked.
+        rune.validate(self.to);
+    }
+}
+
+// TRIVIA
+
+reffed! {
+    /// A trivia token, which the noder ignores.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub enum Trivia {
+        Whitespace(token::Whitespace),
+        Comment(token::Comment),
+    }
+}
+
+impl<'a> TriviaRef<'a> {
+    fn try_from_token(token: &'a red::Token) -> Option<Self> {
+        if let Ok(whitespace) = <&token::Whitespace>::try_from(token) {
+            return Some(Self::Whitespace(whitespace));
+        }
+
+        <&token::Comment>::try_from(token).ok().map(Self::Comment)
+    }
+
+    /// Returns the token of this trivia.
+    pub fn token(self) -> &'a red::Token {
+        match self {
+            Self::Whitespace(whitespace) => whitespace,
+            Self::Comment(comment) => comment,
+        }
+    }
+
+    /// Returns the amount of newlines in this trivia. Only whitespace and
+    /// multiline comments can contain newlines.
+    pub fn newline_count(self) -> usize {
+        self.token().text().bytes().filter(|&c| c == b'\n').count()
+    }
+
+    /// Whether if this trivia is whitespace that ends a line.
+    fn is_line_break(self) -> bool {
+        matches!(self, Self::Whitespace(_)) && self.newline_count() > 0
+    }
+}
+
+/// Pushes the trivia after the token up to the end of its line. Returns the
+/// next token if it is on the same line.
+fn push_same_line_trivia<'a>(token: &'a red::Token, to: &mut Vec<TriviaRef<'a>>) -> Option<&'a red::Token> {
+    let mut next = token.next_token();
+
+    while let Some(token) = next
+        && let Some(trivia) = TriviaRef::try_from_token(token)
+    {
+        if trivia.is_line_break() {
+            return None;
+        }
+
+        to.push(trivia);
+        next = token.next_token();
+    }
+
+    next
+}
+
+/// A node with the trivia around it attached.
+///
+/// Trivia is attached by the following rules, which do not depend on the
+/// nodes the noder placed the trivia tokens in:
+///
+/// - The trailing trivia of a node is the trivia after its last token up to the
+///   end of that line. If the node is followed by a `,` or `;` on the same
+///   line, the trivia after that up to the end of the line is also trailing.
+/// - The leading trivia of a node is the trivia before its first token, except
+///   the trivia on the same line as the token before it, which is trailing that
+///   token instead.
+///
+/// ```txt
+/// {
+///   # Leading trivia of `foo = 1`.
+///   foo = 1, # Trailing trivia of `foo = 1`.
+/// }
+/// ```
+pub trait Attached: ops::Deref<Target = red::Node> {
+    /// Returns the trivia before this node, in source order.
+    fn leading_trivia(&self) -> Vec<TriviaRef<'_>> {
+        let mut trivia = Vec::new();
+
+        let Some(first) = self
+            .descendants_with_tokens()
+            .filter_map(red::ElementRef::into_token)
+            .find(|token| !token.kind().is_trivia())
+        else {
+            return trivia;
+        };
+
+        let mut previous = first.prev_token();
+
+        while let Some(token) = previous
+            && let Some(item) = TriviaRef::try_from_token(token)
+        {
+            trivia.push(item);
+            previous = token.prev_token();
+        }
+
+        if previous.is_some() {
+            let trailing = trivia.iter().rev().take_while(|item| !item.is_line_break()).count();
+            trivia.truncate(trivia.len() - trailing);
+        }
+
+        trivia.reverse();
+        trivia
+    }
+
+    /// Returns the trivia after this node, in source order.
+    fn trailing_trivia(&self) -> Vec<TriviaRef<'_>> {
+        let mut trivia = Vec::new();
+
+        let Some(last) = self
+            .descendants_with_tokens()
+            .filter_map(red::ElementRef::into_token)
+            .filter(|token| !token.kind().is_trivia())
+            .last()
+        else {
+            return trivia;
+        };
+
+        if let Some(next) = push_same_line_trivia(last, &mut trivia)
+            && matches!(next.kind(), TOKEN_COMMA | TOKEN_SEMICOLON)
+        {
+            push_same_line_trivia(next, &mut trivia);
+        }
+
+        trivia
+    }
+}
+
+impl<T: ops::Deref<Target = red::Node>> Attached for T {}
+
 // ERROR
 
 node! {
@@ -374,22 +558,21 @@ impl Parenthesis {
     get_token! { token_parenthesis_right -> Option<TOKEN_PARENTHESIS_RIGHT> }
 
     pub fn validate(&self, to: &mut Vec<Report>) {
-        match self.expression() {
-            Some(expression) => {
-                expression.validate(to);
-            },
-
-            None => {
-                to.push(Report::error("parenthesis without inner expression").primary(
-                    Span::empty(self.token_parenthesis_left().span().end),
-                    "expeted an expression here",
-                ))
-            },
+        if self.expression().is_none() {
+            to.push(
+                Report::error("parenthesis without inner expression")
+                    .code(code::PARENTHESIS_EMPTY)
+                    .primary(
+                        Span::empty(self.token_parenthesis_left().span().end),
+                        "expeted an expression here",
+                    ),
+            )
         }
 
         if self.token_parenthesis_right().is_none() {
             to.push(
                 Report::error("unclosed parenthesis")
+                    .code(code::PARENTHESIS_UNCLOSED)
                     .primary(Span::empty(self.span().end), "expected ')' here")
                     .secondary(self.token_parenthesis_left().span(), "unclosed '(' here"),
             );
@@ -417,23 +600,25 @@ impl List {
         self.expression().into_iter().flat_map(ExpressionRef::same_items)
     }
 
-    pub fn validate(&self, to: &mut Vec<Report>) {
+    /// Validates the inner expression of this list. Reported before the items
+    /// are validated.
+    fn validate_expression(&self, to: &mut Vec<Report>) {
         if let Some(ExpressionRef::InfixOperation(operation)) = self.expression()
             && operation.operator() == InfixOperator::Sequence
         {
             to.push(
                 Report::error("inner expression of list cannot be sequence")
+                    .code(code::LIST_SEQUENCE)
                     .primary(operation.span(), "consider parenthesizing this"),
             );
         }
+    }
 
-        for item in self.items() {
-            item.validate(to);
-        }
-
+    pub fn validate(&self, to: &mut Vec<Report>) {
         if self.token_bracket_right().is_none() {
             to.push(
                 Report::error("unclosed list")
+                    .code(code::LIST_UNCLOSED)
                     .primary(Span::empty(self.span().end), "expected ']' here")
                     .secondary(self.token_bracket_left().span(), "unclosed '[' here"),
             );
@@ -462,6 +647,7 @@ impl Attributes {
         if self.token_curlybrace_right().is_none() {
             to.push(
                 Report::error("unclosed attributes")
+                    .code(code::ATTRIBUTES_UNCLOSED)
                     .primary(Span::empty(self.span().end), "expected '}' here")
                     .secondary(self.token_curlybrace_left().span(), "unclosed '{' here"),
             );
@@ -534,10 +720,6 @@ impl PrefixOperation {
             .find_map(|token| PrefixOperator::try_from(token.kind()).ok())
             .unwrap()
     }
-
-    pub fn validate(&self, to: &mut Vec<Report>) {
-        self.right().validate(to);
-    }
 }
 
 // INFIX OPERATION
@@ -701,26 +883,27 @@ impl InfixOperation {
     }
 
     pub fn validate(&self, to: &mut Vec<Report>) {
-        let expressions = &[self.left(), self.right()];
-
-        for expression in expressions {
-            expression.validate(to);
-        }
-
         let operator = self.operator();
         let (InfixOperator::Apply | InfixOperator::Pipe) = operator else {
             return;
         };
 
-        for expression in expressions {
+        for expression in [self.left(), self.right()] {
             if let ExpressionRef::InfixOperation(operation) = expression
                 && let child_operator @ (InfixOperator::Apply | InfixOperator::Pipe) = operation.operator()
                 && child_operator != operator
             {
                 to.push(
                     Report::error("application and pipe operators do not associate")
+                        .code(code::APPLY_PIPE_MIXED)
                         .secondary(self.span(), "this")
-                        .primary(operation.span(), "does not associate with this"),
+                        .primary(operation.span(), "does not associate with this")
+                        .suggestion(Suggestion::new(
+                            operation.span(),
+                            format!("({operation})", operation = operation.text()),
+                            "add parentheses to make the grouping explicit",
+                            Applicability::MachineApplicable,
+                        )),
                 );
             }
         }
@@ -773,10 +956,6 @@ impl SuffixOperation {
             .find_map(|token| SuffixOperator::try_from(token.kind()).ok())
             .unwrap()
     }
-
-    pub fn validate(&self, to: &mut Vec<Report>) {
-        self.left().validate(to);
-    }
 }
 
 // INTERPOLATION
@@ -820,10 +999,6 @@ impl Interpolation {
     get_node! { expression -> 0 @ ExpressionRef<'_> }
 
     get_token! { interpolation_token_end -> Option<TOKEN_INTERPOLATION_END> }
-
-    pub fn validate(&self, to: &mut Vec<Report>) {
-        self.expression().validate(to);
-    }
 }
 
 /// A trait that can be implemented on any node that iterates over interpolated
@@ -872,7 +1047,7 @@ impl Island {
     get_node! { header -> &IslandHeader }
 
     pub fn validate(&self, to: &mut Vec<Report>) {
-        let mut report = Report::error("invalid island");
+        let mut report = Report::error("invalid island").code(code::ISLAND_INVALID);
         let mut reported_control_character = false;
 
         for part in self.header().parts() {
@@ -889,10 +1064,6 @@ impl Island {
                     }
                 },
 
-                InterpolatedPartRef::Interpolation(interpolation) => {
-                    interpolation.validate(to);
-                },
-
                 _ => {},
             }
         }
@@ -915,16 +1086,6 @@ node! {
 
 impl Parted for Path {}
 
-impl Path {
-    pub fn validate(&self, to: &mut Vec<Report>) {
-        for part in self.parts() {
-            if let InterpolatedPartRef::Interpolation(interpolation) = part {
-                interpolation.validate(to);
-            }
-        }
-    }
-}
-
 // BIND
 
 node! {
@@ -941,10 +1102,8 @@ impl Bind {
     pub fn validate(&self, to: &mut Vec<Report>) {
         let identifier = self.identifier();
 
-        if let ExpressionRef::Identifier(identifier) = identifier {
-            identifier.validate(to);
-        } else if identifier.kind() != NODE_ERROR {
-            to.push(Report::error("invalid bind").primary(
+        if !matches!(identifier, ExpressionRef::Identifier(_)) && identifier.kind() != NODE_ERROR {
+            to.push(Report::error("invalid bind").code(code::BIND_INVALID).primary(
                 identifier.span(),
                 format!("expected an identifier, not {kind}", kind = identifier.kind()),
             ));
@@ -964,7 +1123,7 @@ impl Parted for IdentifierQuoted {}
 
 impl IdentifierQuoted {
     pub fn validate(&self, to: &mut Vec<Report>) {
-        let mut report = Report::error("invalid identifier");
+        let mut report = Report::error("invalid identifier").code(code::IDENTIFIER_INVALID);
         let mut reported_control_character = false;
 
         for part in self.parts() {
@@ -983,10 +1142,6 @@ impl IdentifierQuoted {
                     }
                 },
 
-                InterpolatedPartRef::Interpolation(interpolation) => {
-                    interpolation.validate(to);
-                },
-
                 _ => {},
             }
         }
@@ -1056,7 +1211,7 @@ impl SString {
     // What a behemoth. And the sad part is I can't figure out a way to make this
     // simpler.
     pub fn validate(&self, to: &mut Vec<Report>) {
-        let mut report = Report::error("invalid string");
+        let mut report = Report::error("invalid string").code(code::STRING_INVALID);
         let mut reported_mixed_indentation = false;
 
         let mut parts = self
@@ -1084,8 +1239,6 @@ impl SString {
 
             match &part {
                 InterpolatedPartRef::Interpolation(interpolation) => {
-                    interpolation.validate(to);
-
                     let span = interpolation.span();
 
                     if part_is_first {
@@ -1194,7 +1347,7 @@ impl Parted for Rune {}
 
 impl Rune {
     pub fn validate(&self, to: &mut Vec<Report>) {
-        let mut report = Report::error("invalid rune");
+        let mut report = Report::error("invalid rune").code(code::RUNE_INVALID);
         let mut reported_invalid_len = false;
         let mut reported_control_character = false;
         let mut reported_interpolation = false;
@@ -1302,10 +1455,82 @@ impl If {
     get_token! { token_else -> Option<TOKEN_KEYWORD_ELSE> }
 
     get_node! { alternative -> 2 @ ExpressionRef<'_> }
+}
 
-    pub fn validate(&self, to: &mut Vec<Report>) {
-        self.condition().validate(to);
-        self.consequence().validate(to);
-        self.alternative().validate(to);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        oracle,
+        query::Selector,
+        tokenize,
+    };
+
+    #[test]
+    fn trivia() {
+        let source = "{\n  # foo\n  foo = 1, # after foo\n\n  #= bar =#\n  bar = 2 # after bar\n}";
+        let parse = oracle().parse(tokenize(source));
+
+        let selector: Selector = "NODE_INFIX_OPERATION[operator=Equal]".parse().unwrap();
+
+        let comments = |trivia: Vec<TriviaRef<'_>>| -> Vec<String> {
+            trivia
+                .into_iter()
+                .filter_map(|item| {
+                    match item {
+                        TriviaRef::Comment(comment) => Some(comment.text().to_owned()),
+                        TriviaRef::Whitespace(_) => None,
+                    }
+                })
+                .collect()
+        };
+
+        let bindings: Vec<_> = selector
+            .select(&parse.node)
+            .map(|node| ExpressionRef::try_from(node).unwrap())
+            .collect();
+
+        let [foo, bar] = bindings[..] else {
+            panic!("expected two bindings, got {bindings:?}");
+        };
+
+        assert_eq!(comments(foo.leading_trivia()), ["# foo"]);
+        assert_eq!(comments(foo.trailing_trivia()), ["# after foo"]);
+
+        assert_eq!(comments(bar.leading_trivia()), ["#= bar =#"]);
+        assert_eq!(comments(bar.trailing_trivia()), ["# after bar"]);
+
+        assert_eq!(
+            bar.leading_trivia()
+                .into_iter()
+                .map(TriviaRef::newline_count)
+                .collect::<Vec<_>>(),
+            [2, 0, 1],
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let codes = |source: &str| -> Vec<String> {
+            let parse = oracle().parse(tokenize(source));
+
+            let mut reports = Vec::new();
+            parse.expression.as_ref().validate(&mut reports);
+
+            reports
+                .into_iter()
+                .filter_map(|report| report.code.map(String::from))
+                .collect()
+        };
+
+        // The inner expression of a list is reported before the items, and
+        // the list being unclosed after.
+        assert_eq!(
+            codes("[a; b <| c |> d"),
+            [code::LIST_SEQUENCE, code::APPLY_PIPE_MIXED, code::LIST_UNCLOSED],
+        );
+
+        // The expressions inside attributes are not validated.
+        assert_eq!(codes("{ foo = a <| b |> c"), [code::ATTRIBUTES_UNCLOSED]);
     }
 }
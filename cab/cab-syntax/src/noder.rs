@@ -5,11 +5,14 @@ use std::{
 };
 
 use cab_why::{
+    Bias,
+    EditSet,
     IntoSize,
     Report,
     ReportSeverity,
     Size,
     Span,
+    TextEdit,
 };
 use enumset::EnumSet;
 use peekmore::{
@@ -22,9 +25,12 @@ use crate::{
         self,
         *,
     },
+    build,
+    code,
     green,
     node,
     red,
+    tokenize,
 };
 
 /// A parse result that contains a node, a [`node::Expression`] and a
@@ -56,6 +62,33 @@ impl Parse {
             Err(self.reports)
         }
     }
+
+    /// Returns the span of the innermost node that covers the span of the
+    /// given report. Meant to be used as the key to merge cascaded reports
+    /// with [`Postprocessor::process`].
+    ///
+    /// Returns [`None`] if that node covers the whole source, as unrelated
+    /// reports at the top level would all be merged otherwise.
+    ///
+    /// [`Postprocessor::process`]: cab_why::Postprocessor::process
+    pub fn node_of(&self, report: &Report) -> Option<Span> {
+        let span = report.span()?;
+
+        if !Span::from(self.node.text_range()).contains(span) {
+            return None;
+        }
+
+        let node = match self.node.covering_element(span.into()) {
+            cstree::util::NodeOrToken::Node(node) => node,
+            cstree::util::NodeOrToken::Token(token) => token.parent(),
+        };
+
+        if node.parent().is_none() || node.text_range() == self.node.text_range() {
+            return None;
+        }
+
+        Some(node.text_range().into())
+    }
 }
 
 /// A parse oracle that holds a cache for token deduplication.
@@ -79,15 +112,6 @@ impl Oracle {
             this.next_expect(EnumSet::empty(), EnumSet::empty());
         });
 
-        let (green_node, _) = noder.builder.finish();
-
-        let node = red::Node::new_root_with_resolver(green_node, Arc::clone(self.cache.interner()));
-
-        let expression = node
-            .first_child()
-            .and_then(|node| node::Expression::try_from(node.clone()).ok())
-            .unwrap();
-
         noder.reports.retain({
             let mut last_span = None;
 
@@ -105,12 +129,159 @@ impl Oracle {
             }
         });
 
-        expression.as_ref().validate(&mut noder.reports);
+        let (green_node, _) = noder.builder.finish();
+
+        self.finish(green_node, noder.reports)
+    }
+
+    /// Reparses the source of the given parse with the edit applied. The result
+    /// is identical to a full parse of the edited source.
+    ///
+    /// Only the innermost parenthesis, list or attributes that contains the
+    /// edit is retokenized and reparsed, and the rest of the tree is reused.
+    /// Falls back to a full parse if that is not possible, such as when the
+    /// edit is not inside any of them or changes the errors in it.
+    pub fn reparse(&self, parse: &Parse, edit: &TextEdit) -> Parse {
+        if let Some(reparse) = self.reparse_delimited(parse, edit) {
+            return reparse;
+        }
+
+        let source = EditSet::from(edit.clone()).apply(&parse.node.text().to_string());
+
+        self.parse(tokenize(&source))
+    }
+
+    fn reparse_delimited(&self, parse: &Parse, edit: &TextEdit) -> Option<Parse> {
+        let span = edit.span;
+
+        // The reports of the noder come before the reports of validation.
+        let mut validation_reports = Vec::new();
+        parse.expression.as_ref().validate(&mut validation_reports);
+        let noder_reports = &parse.reports[..parse.reports.len().checked_sub(validation_reports.len())?];
+
+        let covering = match parse.node.covering_element(span.into()) {
+            cstree::util::NodeOrToken::Node(node) => node,
+            cstree::util::NodeOrToken::Token(token) => token.parent(),
+        };
+
+        for node in covering.ancestors() {
+            let node_span = Span::from(node.text_range());
+
+            let closing = match node.kind() {
+                NODE_PARENTHESIS => TOKEN_PARENTHESIS_RIGHT,
+                NODE_LIST => TOKEN_BRACKET_RIGHT,
+                NODE_ATTRIBUTES => TOKEN_CURLYBRACE_RIGHT,
+                _ => continue,
+            };
+
+            // The node must be closed and the edit must be between its
+            // delimiters, as they are what make it parse the same in any
+            // context.
+            if node.last_token().map(|token| token.kind()) != Some(closing)
+                || span.start <= node_span.start
+                || node_span.end <= span.end
+            {
+                continue;
+            }
+
+            // Recovering from errors depends on the context.
+            if noder_reports.iter().any(|report| {
+                report
+                    .labels
+                    .iter()
+                    .any(|label| label.span.start <= node_span.end && node_span.start <= label.span.end)
+            }) {
+                continue;
+            }
+
+            let old_text = node.text().to_string();
+
+            // The tokenizer might be in a different state in the context, such
+            // as inside a string.
+            if !tokenize(&old_text).eq(node
+                .descendants_with_tokens()
+                .filter_map(red::ElementRef::into_token)
+                .map(|token| (token.kind(), token.text())))
+            {
+                continue;
+            }
+
+            let mut text = old_text;
+            text.replace_range(
+                Span::std(span.start - node_span.start, span.end - node_span.start),
+                &edit.replacement,
+            );
+
+            let mut noder = Noder::with_interner_and_tokens(Arc::clone(self.cache.interner()), tokenize(&text));
+
+            match node.kind() {
+                NODE_PARENTHESIS => noder.node_parenthesis(EnumSet::empty()),
+                NODE_LIST => noder.node_list(EnumSet::empty()),
+                _ => noder.node_attributes(EnumSet::empty()),
+            }
+
+            if !noder.reports.is_empty()
+                || noder.tokens.peek().is_some()
+                || tokenize(&text).last().map(|(kind, _)| kind) != Some(closing)
+            {
+                continue;
+            }
+
+            let (green_node, _) = noder.builder.finish();
+
+            // The reports left are all outside the node, so they are only moved.
+            let edits = EditSet::from(edit.clone());
+
+            let reports = noder_reports
+                .iter()
+                .cloned()
+                .map(|mut report| {
+                    for label in &mut report.labels {
+                        label.span = edits.map_span(label.span, Bias::Right);
+                    }
+
+                    for suggestion in &mut report.suggestions {
+                        suggestion.span = edits.map_span(suggestion.span, Bias::Right);
+                    }
+
+                    report
+                })
+                .collect();
+
+            return Some(self.finish(node.replace_with(green_node), reports));
+        }
+
+        None
+    }
+
+    /// Builds the given expression into a [`Parse`]. Only validation can report
+    /// issues, as built expressions are always well-formed.
+    pub fn build(&self, expression: &build::Expression) -> Parse {
+        let mut builder = green::NodeBuilder::from_interner(Arc::clone(self.cache.interner()));
+
+        builder.start_node(NODE_ROOT);
+        expression.write(&mut builder);
+        builder.finish_node();
+
+        let (green_node, _) = builder.finish();
+
+        self.finish(green_node, Vec::new())
+    }
+
+    fn finish(&self, green_node: green::Node, mut reports: Vec<Report>) -> Parse {
+        let node = red::Node::new_root_with_resolver(green_node, Arc::clone(self.cache.interner()));
+
+        let expression = node
+            .first_child()
+            .and_then(|node| node::Expression::try_from(node.clone()).ok())
+            .unwrap();
+
+        expression.as_ref().validate(&mut reports);
 
         Parse {
             node,
             expression,
-            reports: noder.reports,
+            reports,
         }
     }
 }
@@ -119,7 +290,8 @@ fn unexpected(got: Option<Kind>, mut expected: EnumSet<Kind>, span: Span) -> Rep
     let report = match got {
         Some(kind) => Report::error(format!("didn't expect {kind}")),
         None => Report::error("didn't expect end of file"),
-    };
+    }
+    .code(code::UNEXPECTED);
 
     let mut reason = if expected.is_empty() {
         return report.primary(span, "expected end of file");
@@ -595,3 +767,66 @@ impl<'a, I: Iterator<Item = (Kind, &'a str)>> Noder<'a, I> {
         self.node_expression_binding_power(0, until);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCES: &[&str] = &[
+        "[ 1, 2, (3 + 4) * 5 ]",
+        "{ foo = [ 1, 2 ], bar = { baz = \"qux \\(1 + 2)\" } }",
+        "(x => [ x, (x), { y = x } ]) 42",
+        "[ [ [ ] ] ] ++ [ ({ }) ]",
+    ];
+
+    const REPLACEMENTS: &[&str] = &["", " ", "1", "x", ",", "(", ")", "[", "]", "{", "}", "\"", "\\(", "# "];
+
+    #[test]
+    fn reparse_is_identical_to_parse() {
+        let oracle = oracle();
+
+        // Xorshift, to get reproducible edits without a dependency.
+        let mut state = 0x2545_F491_4F6C_DD1D_u64;
+        let mut random = |below: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % below as u64) as usize
+        };
+
+        for source in SOURCES {
+            let mut source = source.to_string();
+            let mut parse = oracle.parse(tokenize(&source));
+
+            for _ in 0..200 {
+                let start = random(source.len() + 1);
+                let end = start + random(source.len() - start + 1).min(3);
+                let replacement = REPLACEMENTS[random(REPLACEMENTS.len())];
+
+                let reparse = oracle.reparse(&parse, &TextEdit::replace(start..end, replacement));
+
+                source.replace_range(start..end, replacement);
+                let full = oracle.parse(tokenize(&source));
+
+                assert_eq!(
+                    format!("{:#?}", reparse.node),
+                    format!("{:#?}", full.node),
+                    "{source:?}"
+                );
+                assert_eq!(
+                    format!("{:?}", reparse.reports),
+                    format!("{:?}", full.reports),
+                    "{source:?}"
+                );
+
+                parse = reparse;
+
+                // Keep the sources small and mostly valid.
+                if source.len() > 80 || !parse.reports.is_empty() && random(4) == 0 {
+                    source = SOURCES[random(SOURCES.len())].to_string();
+                    parse = oracle.parse(tokenize(&source));
+                }
+            }
+        }
+    }
+}
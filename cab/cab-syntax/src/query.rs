@@ -0,0 +1,426 @@
+//! Selectors that match nodes in a syntax tree.
+//!
+//! A selector is a sequence of compound selectors separated by combinators,
+//! similar to CSS selectors:
+//!
+//! ```txt
+//! NODE_ATTRIBUTES > NODE_INFIX_OPERATION[operator=Equal]
+//! NODE_BIND NODE_IDENTIFIER[identifier=inputs]
+//! *[operator=Select]
+//! ```
+//!
+//! A compound selector is a [`Kind`] name or `*` to match any kind, followed
+//! by any amount of attributes:
+//!
+//! - `[operator=Name]` matches prefix, infix and suffix operations with the
+//!   given operator, such as `Select` or `Negation`.
+//! - `[identifier=name]` matches identifiers with the given value. The value of
+//!   a quoted identifier is its content without the delimiters, and quoted
+//!   identifiers with interpolations never match.
+//!
+//! Attribute values may be quoted with `"` to include `]` or whitespace.
+//!
+//! Compound selectors separated by `>` match a child of a node, while ones
+//! separated by whitespace match a descendant. The `Same` and `Sequence`
+//! operations that chain items together are looked through by `>`, so
+//! `NODE_ATTRIBUTES > *` matches every binding of attributes, not the chain
+//! of `,` operations they are in. The operations inside the chain match as
+//! well.
+use std::str::FromStr;
+
+use cab_why::{
+    Report,
+    Span,
+};
+use enumset::EnumSet;
+
+use crate::{
+    Kind,
+    node::{
+        self,
+        Parted as _,
+    },
+    red,
+};
+
+/// The relation between a compound selector and the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    /// The node must be a child of the node the previous selector matched,
+    /// ignoring the `Same` and `Sequence` operations between them.
+    Child,
+    /// The node must be a descendant of the node the previous selector
+    /// matched.
+    Descendant,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Attribute {
+    Operator(String),
+    Identifier(String),
+}
+
+impl Attribute {
+    fn matches(&self, node: &red::Node) -> bool {
+        match self {
+            Self::Operator(name) => {
+                let operator = if let Ok(operation) = <&node::PrefixOperation>::try_from(node) {
+                    format!("{:?}", operation.operator())
+                } else if let Ok(operation) = <&node::InfixOperation>::try_from(node) {
+                    format!("{:?}", operation.operator())
+                } else if let Ok(operation) = <&node::SuffixOperation>::try_from(node) {
+                    format!("{:?}", operation.operator())
+                } else {
+                    return false;
+                };
+
+                operator == *name
+            },
+
+            Self::Identifier(value) => {
+                let Ok(identifier) = <&node::Identifier>::try_from(node) else {
+                    return false;
+                };
+
+                match identifier.value() {
+                    node::IdentifierValueRef::Plain(token) => token.text() == *value,
+
+                    node::IdentifierValueRef::Quoted(quoted) => {
+                        let mut content = String::new();
+
+                        for part in quoted.parts() {
+                            match part {
+                                node::InterpolatedPartRef::Delimiter(_) => {},
+                                node::InterpolatedPartRef::Content(token) => content.push_str(token.text()),
+                                node::InterpolatedPartRef::Interpolation(_) => return false,
+                            }
+                        }
+
+                        content == *value
+                    },
+                }
+            },
+        }
+    }
+}
+
+/// Whether if the node is a `Same` or `Sequence` operation, which chain items
+/// together.
+fn is_chain(node: &red::Node) -> bool {
+    if let Ok(operation) = <&node::InfixOperation>::try_from(node) {
+        matches!(
+            operation.operator(),
+            node::InfixOperator::Same | node::InfixOperator::Sequence
+        )
+    } else if let Ok(operation) = <&node::SuffixOperation>::try_from(node) {
+        matches!(
+            operation.operator(),
+            node::SuffixOperator::Same | node::SuffixOperator::Sequence
+        )
+    } else {
+        false
+    }
+}
+
+/// Returns the parent of the node, looking through the chains around it.
+fn chain_parent(node: &red::Node) -> Option<&red::Node> {
+    let mut parent = node.parent()?;
+
+    while is_chain(parent) {
+        parent = parent.parent()?;
+    }
+
+    Some(parent)
+}
+
+/// A kind and attributes that a single node must match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Compound {
+    /// The kind to match. [`None`] matches any kind.
+    kind: Option<Kind>,
+    attributes: Vec<Attribute>,
+}
+
+impl Compound {
+    fn matches(&self, node: &red::Node) -> bool {
+        self.kind.is_none_or(|kind| node.kind() == kind)
+            && self.attributes.iter().all(|attribute| attribute.matches(node))
+    }
+}
+
+/// A parsed selector. See the [module documentation](self) for the syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    /// The compound selectors, each with the combinator that relates it to
+    /// the previous one. The combinator of the first one is unused.
+    compounds: Vec<(Combinator, Compound)>,
+}
+
+impl FromStr for Selector {
+    type Err = Report;
+
+    fn from_str(selector: &str) -> Result<Self, Report> {
+        Parser {
+            source: selector,
+            offset: 0,
+        }
+        .selector()
+    }
+}
+
+impl Selector {
+    /// Whether if the given node matches this selector.
+    pub fn matches(&self, node: &red::Node) -> bool {
+        self.matches_up_to(self.compounds.len(), node)
+    }
+
+    /// Returns all nodes in the given tree that match this selector, in
+    /// preorder. The root itself is included.
+    pub fn select<'a>(&'a self, root: &'a red::Node) -> impl Iterator<Item = &'a red::Node> {
+        root.descendants().filter(|node| self.matches(node))
+    }
+
+    /// Whether if the given node matches the first `count` compound selectors.
+    fn matches_up_to(&self, count: usize, node: &red::Node) -> bool {
+        let Some((combinator, compound)) = count.checked_sub(1).map(|index| &self.compounds[index]) else {
+            return true;
+        };
+
+        if !compound.matches(node) {
+            return false;
+        }
+
+        if count == 1 {
+            return true;
+        }
+
+        match combinator {
+            Combinator::Child => chain_parent(node).is_some_and(|parent| self.matches_up_to(count - 1, parent)),
+
+            Combinator::Descendant => {
+                let mut ancestor = node.parent();
+
+                while let Some(node) = ancestor {
+                    if self.matches_up_to(count - 1, node) {
+                        return true;
+                    }
+
+                    ancestor = node.parent();
+                }
+
+                false
+            },
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Returns the span of the next char, which is empty at the end.
+    fn peek_span(&self) -> Span {
+        Span::at(self.offset, self.peek().map_or(0, char::len_utf8))
+    }
+
+    /// Skips whitespace and returns whether if there was any.
+    fn whitespace(&mut self) -> bool {
+        let start = self.offset;
+        self.offset += self.rest().len() - self.rest().trim_start().len();
+
+        self.offset != start
+    }
+
+    /// Consumes chars while the predicate holds and returns them.
+    fn consume_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let consumed = &rest[..rest.find(|c| !predicate(c)).unwrap_or(rest.len())];
+
+        self.offset += consumed.len();
+        consumed
+    }
+
+    fn expect(&mut self, expected: char, title: &'static str) -> Result<(), Report> {
+        if self.peek() != Some(expected) {
+            return Err(Report::error(title).primary(self.peek_span(), format!("expected '{expected}' here")));
+        }
+
+        self.offset += expected.len_utf8();
+        Ok(())
+    }
+
+    fn selector(mut self) -> Result<Selector, Report> {
+        self.whitespace();
+
+        let mut compounds = vec![(Combinator::Descendant, self.compound()?)];
+
+        loop {
+            let spaced = self.whitespace();
+
+            let combinator = match self.peek() {
+                None => break,
+
+                Some('>') => {
+                    self.offset += 1;
+                    self.whitespace();
+
+                    Combinator::Child
+                },
+
+                Some(_) if spaced => Combinator::Descendant,
+
+                Some(_) => {
+                    return Err(Report::error("expected a combinator")
+                        .primary(self.peek_span(), "unexpected character")
+                        .help("separate selectors with '>' to match children or whitespace to match descendants"));
+                },
+            };
+
+            compounds.push((combinator, self.compound()?));
+        }
+
+        Ok(Selector { compounds })
+    }
+
+    fn compound(&mut self) -> Result<Compound, Report> {
+        let kind = if self.peek() == Some('*') {
+            self.offset += 1;
+            None
+        } else {
+            let start = self.offset;
+            let name = self.consume_while(|c| c.is_ascii_alphanumeric() || c == '_');
+
+            if name.is_empty() {
+                return Err(
+                    Report::error("expected a selector").primary(self.peek_span(), "expected a kind or '*' here")
+                );
+            }
+
+            let Some(kind) = EnumSet::<Kind>::all().iter().find(|kind| format!("{kind:?}") == name) else {
+                return Err(Report::error("unknown kind")
+                    .primary(Span::new(start, self.offset), "no kind with this name")
+                    .help("kinds are written as they are in syntax dumps, such as NODE_INFIX_OPERATION"));
+            };
+
+            Some(kind)
+        };
+
+        let mut attributes = Vec::new();
+
+        while self.peek() == Some('[') {
+            self.offset += 1;
+            attributes.push(self.attribute()?);
+        }
+
+        Ok(Compound { kind, attributes })
+    }
+
+    fn attribute(&mut self) -> Result<Attribute, Report> {
+        self.whitespace();
+
+        let key_start = self.offset;
+        let key = self.consume_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        let key_span = Span::new(key_start, self.offset);
+
+        self.whitespace();
+        self.expect('=', "expected '=' after attribute name")?;
+        self.whitespace();
+
+        let value = if self.peek() == Some('"') {
+            let start = self.offset;
+            self.offset += 1;
+
+            let value = self.consume_while(|c| c != '"');
+
+            if self.peek().is_none() {
+                return Err(Report::error("unclosed attribute value")
+                    .primary(Span::new(start, self.offset), "missing closing '\"'"));
+            }
+
+            self.offset += 1;
+            value
+        } else {
+            self.consume_while(|c| c != ']' && !c.is_whitespace())
+        };
+
+        self.whitespace();
+        self.expect(']', "unclosed attribute")?;
+
+        match key {
+            "operator" => Ok(Attribute::Operator(value.to_owned())),
+            "identifier" => Ok(Attribute::Identifier(value.to_owned())),
+
+            _ => {
+                Err(Report::error("unknown attribute")
+                    .primary(key_span, "no attribute with this name")
+                    .help("valid attributes are 'operator' and 'identifier'"))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        oracle,
+        tokenize,
+    };
+
+    #[track_caller]
+    fn assert_selects(selector: &str, source: &str, expected: &[&str]) {
+        let selector = selector.parse::<Selector>().unwrap();
+        let parse = oracle().parse(tokenize(source));
+
+        let selected: Vec<_> = selector
+            .select(&parse.node)
+            .map(|node| node.text().to_string().trim().to_owned())
+            .collect();
+
+        assert_eq!(selected, expected);
+    }
+
+    #[test]
+    fn select() {
+        assert_selects("NODE_INTEGER", "[ 1, 2 ]", &["1", "2"]);
+        assert_selects("NODE_PARENTHESIS > NODE_INTEGER", "[ (1), (-2) ]", &["1"]);
+        assert_selects("NODE_PARENTHESIS NODE_INTEGER", "[ (1), (-2) ]", &["1", "2"]);
+
+        assert_selects(
+            "NODE_ATTRIBUTES > NODE_INFIX_OPERATION[operator=Equal]",
+            "{ foo = 1 }",
+            &["foo = 1"],
+        );
+        assert_selects(
+            "NODE_ATTRIBUTES > NODE_INFIX_OPERATION[operator=Equal]",
+            "{ foo = 1, bar = 2, }",
+            &["foo = 1", "bar = 2"],
+        );
+        assert_selects("NODE_LIST > NODE_INTEGER", "[ 1, (2), 3; 4 ]", &["1", "3", "4"]);
+        assert_selects("*[operator=Select]", "foo.bar", &["foo.bar"]);
+
+        assert_selects("NODE_IDENTIFIER[identifier=foo]", "foo bar `foo`", &["foo", "`foo`"]);
+        assert_selects(
+            r#"NODE_BIND > NODE_IDENTIFIER[identifier="in puts"]"#,
+            "@`in puts` => `in puts`",
+            &["`in puts`"],
+        );
+    }
+
+    #[test]
+    fn errors() {
+        for selector in ["", "NODE_FOO", "NODE_LIST[", "NODE_LIST[kind=x]", "NODE_LIST]", "* >"] {
+            assert!(selector.parse::<Selector>().is_err(), "{selector:?} parsed");
+        }
+    }
+}
@@ -7,10 +7,12 @@ use std::{
 };
 
 use cab_why::{
+    Applicability,
     IntoSpan,
     Label,
     Report,
     Span,
+    Suggestion,
 };
 use num::Num as _;
 
@@ -82,7 +84,11 @@ token! {
 }
 
 impl Whitespace {
-    /// Returns the amount of lines this whitespace.
+    /// Returns the amount of lines this whitespace spans, which is one more
+    /// than the amount of newlines in it. See [`TriviaRef::newline_count`] for
+    /// the amount of newlines.
+    ///
+    /// [`TriviaRef::newline_count`]: crate::node::TriviaRef::newline_count
     pub fn newline_count(&self) -> usize {
         self.text().bytes().filter(|&c| c == b'\n').count() + 1
     }
@@ -192,6 +198,13 @@ impl Content {
                             "invalid escape",
                         ));
 
+                        report.push_suggestion(Suggestion::new(
+                            Span::at(self.span().start + offset, 1u32),
+                            r"\\",
+                            "escape the backslash to keep it literally",
+                            Applicability::MachineApplicable,
+                        ));
+
                         report.push_tip(r#"escapes must be one of: \0, \t, \n, \r, \`, \", \', \>, \\"#);
 
                         continue;
@@ -1,5 +1,9 @@
-use std::assert_matches::assert_matches;
+use std::{
+    assert_matches::assert_matches,
+    iter,
+};
 
+use cab_why::Span;
 use smallvec::SmallVec;
 
 use crate::Kind::{
@@ -12,6 +16,19 @@ pub fn tokenize(source: &str) -> impl Iterator<Item = (Kind, &str)> {
     Tokenizer::new(source)
 }
 
+/// Returns an iterator of tokens that reference the given string, along with
+/// their spans in it.
+pub fn tokenize_spanned(source: &str) -> impl Iterator<Item = (Kind, &str, Span)> {
+    let mut tokenizer = Tokenizer::new(source);
+
+    iter::from_fn(move || {
+        let start = tokenizer.offset;
+        let (kind, slice) = tokenizer.next()?;
+
+        Some((kind, slice, Span::at(start, slice.len())))
+    })
+}
+
 fn is_valid_initial_identifier_character(c: char) -> bool {
     let invalid = c.is_ascii_digit() || c == '-' || c == '\'';
 
@@ -567,6 +584,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn spans() {
+        let source = r#""a\(b)" # c"#;
+
+        for (_, slice, span) in tokenize_spanned(source) {
+            assert_eq!(&source[span.as_std()], slice);
+        }
+
+        assert_eq!(
+            tokenize_spanned(source).last().map(|(_, _, span)| span),
+            Some(Span::new(8u32, 11u32)),
+        );
+    }
+
     #[test]
     fn errors_are_individual() {
         assert_token_matches!(
@@ -0,0 +1,295 @@
+//! Traversal and rewriting of [`node::Expression`]s.
+//!
+//! A [`Visitor`] has a method for every node, which walks its children by
+//! default. Overriding a method and calling the matching `walk_*` function
+//! from it runs code around the default traversal:
+//!
+//! ```rs
+//! struct Counter(usize);
+//!
+//! impl<'a> Visitor<'a> for Counter {
+//!     fn visit_list(&mut self, list: &'a node::List) {
+//!         self.0 += 1;
+//!         visit::walk_list(self, list);
+//!     }
+//! }
+//! ```
+use crate::{
+    green,
+    node::{
+        self,
+        ExpressionRef,
+        InterpolatedPartRef,
+        Parted as _,
+    },
+    red,
+};
+
+/// A visitor over [`node::ExpressionRef`]s and the nodes inside them.
+///
+/// Every method walks the children of its node by default, so implementors
+/// only need to override the methods of the nodes they care about.
+pub trait Visitor<'a> {
+    fn visit_expression(&mut self, expression: ExpressionRef<'a>) {
+        walk_expression(self, expression);
+    }
+
+    fn visit_error(&mut self, _error: &'a node::Error) {}
+
+    fn visit_parenthesis(&mut self, parenthesis: &'a node::Parenthesis) {
+        walk_parenthesis(self, parenthesis);
+    }
+
+    fn visit_list(&mut self, list: &'a node::List) {
+        walk_list(self, list);
+    }
+
+    fn visit_attributes(&mut self, attributes: &'a node::Attributes) {
+        walk_attributes(self, attributes);
+    }
+
+    fn visit_prefix_operation(&mut self, operation: &'a node::PrefixOperation) {
+        walk_prefix_operation(self, operation);
+    }
+
+    fn visit_infix_operation(&mut self, operation: &'a node::InfixOperation) {
+        walk_infix_operation(self, operation);
+    }
+
+    fn visit_suffix_operation(&mut self, operation: &'a node::SuffixOperation) {
+        walk_suffix_operation(self, operation);
+    }
+
+    fn visit_interpolation(&mut self, interpolation: &'a node::Interpolation) {
+        walk_interpolation(self, interpolation);
+    }
+
+    fn visit_island(&mut self, island: &'a node::Island) {
+        walk_island(self, island);
+    }
+
+    fn visit_path(&mut self, path: &'a node::Path) {
+        walk_path(self, path);
+    }
+
+    fn visit_bind(&mut self, bind: &'a node::Bind) {
+        walk_bind(self, bind);
+    }
+
+    fn visit_identifier(&mut self, identifier: &'a node::Identifier) {
+        walk_identifier(self, identifier);
+    }
+
+    fn visit_string(&mut self, string: &'a node::SString) {
+        walk_string(self, string);
+    }
+
+    fn visit_rune(&mut self, rune: &'a node::Rune) {
+        walk_rune(self, rune);
+    }
+
+    fn visit_integer(&mut self, _integer: &'a node::Integer) {}
+
+    fn visit_float(&mut self, _float: &'a node::Float) {}
+
+    fn visit_if(&mut self, if_else: &'a node::If) {
+        walk_if(self, if_else);
+    }
+}
+
+/// Calls the [`Visitor`] method that matches the expression.
+pub fn walk_expression<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expression: ExpressionRef<'a>) {
+    match expression {
+        ExpressionRef::Error(error) => visitor.visit_error(error),
+
+        ExpressionRef::Parenthesis(parenthesis) => visitor.visit_parenthesis(parenthesis),
+        ExpressionRef::List(list) => visitor.visit_list(list),
+        ExpressionRef::Attributes(attributes) => visitor.visit_attributes(attributes),
+
+        ExpressionRef::PrefixOperation(operation) => visitor.visit_prefix_operation(operation),
+        ExpressionRef::InfixOperation(operation) => visitor.visit_infix_operation(operation),
+        ExpressionRef::SuffixOperation(operation) => visitor.visit_suffix_operation(operation),
+
+        ExpressionRef::Island(island) => visitor.visit_island(island),
+        ExpressionRef::Path(path) => visitor.visit_path(path),
+
+        ExpressionRef::Bind(bind) => visitor.visit_bind(bind),
+        ExpressionRef::Identifier(identifier) => visitor.visit_identifier(identifier),
+        ExpressionRef::SString(string) => visitor.visit_string(string),
+        ExpressionRef::Rune(rune) => visitor.visit_rune(rune),
+
+        ExpressionRef::Integer(integer) => visitor.visit_integer(integer),
+        ExpressionRef::Float(float) => visitor.visit_float(float),
+
+        ExpressionRef::If(if_else) => visitor.visit_if(if_else),
+    }
+}
+
+pub fn walk_parenthesis<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, parenthesis: &'a node::Parenthesis) {
+    if let Some(expression) = parenthesis.expression() {
+        visitor.visit_expression(expression);
+    }
+}
+
+pub fn walk_list<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, list: &'a node::List) {
+    if let Some(expression) = list.expression() {
+        visitor.visit_expression(expression);
+    }
+}
+
+pub fn walk_attributes<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, attributes: &'a node::Attributes) {
+    if let Some(expression) = attributes.expression() {
+        visitor.visit_expression(expression);
+    }
+}
+
+pub fn walk_prefix_operation<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, operation: &'a node::PrefixOperation) {
+    visitor.visit_expression(operation.right());
+}
+
+pub fn walk_infix_operation<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, operation: &'a node::InfixOperation) {
+    visitor.visit_expression(operation.left());
+    visitor.visit_expression(operation.right());
+}
+
+pub fn walk_suffix_operation<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, operation: &'a node::SuffixOperation) {
+    visitor.visit_expression(operation.left());
+}
+
+pub fn walk_interpolation<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, interpolation: &'a node::Interpolation) {
+    visitor.visit_expression(interpolation.expression());
+}
+
+/// Visits the interpolations in the given parts.
+pub fn walk_parts<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, parts: impl Iterator<Item = InterpolatedPartRef<'a>>) {
+    for part in parts {
+        if let InterpolatedPartRef::Interpolation(interpolation) = part {
+            visitor.visit_interpolation(interpolation);
+        }
+    }
+}
+
+pub fn walk_island<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, island: &'a node::Island) {
+    walk_parts(visitor, island.header().parts());
+}
+
+pub fn walk_path<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, path: &'a node::Path) {
+    walk_parts(visitor, path.parts());
+}
+
+pub fn walk_bind<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, bind: &'a node::Bind) {
+    visitor.visit_expression(bind.identifier());
+}
+
+pub fn walk_identifier<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, identifier: &'a node::Identifier) {
+    if let node::IdentifierValueRef::Quoted(quoted) = identifier.value() {
+        walk_parts(visitor, quoted.parts());
+    }
+}
+
+pub fn walk_string<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, string: &'a node::SString) {
+    walk_parts(visitor, string.parts());
+}
+
+pub fn walk_rune<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, rune: &'a node::Rune) {
+    walk_parts(visitor, rune.parts());
+}
+
+pub fn walk_if<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, if_else: &'a node::If) {
+    visitor.visit_expression(if_else.condition());
+    visitor.visit_expression(if_else.consequence());
+    visitor.visit_expression(if_else.alternative());
+}
+
+/// A rewriter that replaces [`node::ExpressionRef`]s with new green nodes.
+pub trait Rewriter {
+    /// Returns the green node to replace the given expression with, or
+    /// [`None`] to keep it and rewrite the expressions inside it instead.
+    fn rewrite_expression(&mut self, expression: ExpressionRef<'_>) -> Option<green::Node>;
+
+    /// Rewrites the given node and returns the new green node. Subtrees that
+    /// have nothing replaced in them are reused as is.
+    fn rewrite(&mut self, node: &red::Node) -> green::Node
+    where
+        Self: Sized,
+    {
+        rewrite_node(self, node).unwrap_or_else(|| node.green().clone())
+    }
+}
+
+/// Returns the rewritten green node, or [`None`] if nothing in it was
+/// replaced.
+fn rewrite_node(rewriter: &mut impl Rewriter, node: &red::Node) -> Option<green::Node> {
+    if let Ok(expression) = ExpressionRef::try_from(node)
+        && let Some(green) = rewriter.rewrite_expression(expression)
+    {
+        return Some(green);
+    }
+
+    let replacements: Vec<_> = node
+        .children()
+        .enumerate()
+        .filter_map(|(index, child)| Some((index, rewrite_node(rewriter, child)?)))
+        .collect();
+
+    if replacements.is_empty() {
+        return None;
+    }
+
+    // Indices of child nodes do not change when one of them is replaced, so
+    // each replacement is done on a fresh tree that is rooted at this node.
+    let mut green = node.green().clone();
+
+    for (index, replacement) in replacements {
+        let root = red::ResolvedNode::new_root(green);
+
+        green = root
+            .children()
+            .nth(index)
+            .expect("replaced child must exist")
+            .replace_with(replacement);
+    }
+
+    Some(green)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Oracle,
+        oracle,
+        tokenize,
+    };
+
+    /// Wraps integers in parentheses.
+    struct Parenthesize<'a>(&'a Oracle);
+
+    impl Rewriter for Parenthesize<'_> {
+        fn rewrite_expression(&mut self, expression: ExpressionRef<'_>) -> Option<green::Node> {
+            let ExpressionRef::Integer(integer) = expression else {
+                return None;
+            };
+
+            let source = format!("({integer})", integer = integer.text());
+            let parse = self.0.parse(tokenize(&source));
+
+            Some(parse.expression.as_ref().green().clone())
+        }
+    }
+
+    #[test]
+    fn rewrite() {
+        let oracle = oracle();
+
+        let parse = oracle.parse(tokenize("[1,foo,[2]]"));
+        let expected = oracle.parse(tokenize("[(1),foo,[(2)]]"));
+
+        assert_eq!(Parenthesize(&oracle).rewrite(&parse.node), *expected.node.green());
+
+        // Nothing is replaced, so the tree is reused.
+        let parse = oracle.parse(tokenize("[foo,bar]"));
+
+        assert_eq!(Parenthesize(&oracle).rewrite(&parse.node), *parse.node.green());
+    }
+}
@@ -18,26 +18,34 @@ use std::{
 
 use yansi::Paint;
 
+use crate::ReportDisplay;
+
 /// A type alias for consice use of [`Error`].
 pub type Result<T> = result::Result<T, Error>;
 
 /// The error type. Stores an error chain that can be appended to with
-/// [`Contextful`]. Can be formatted to show the chain with [`fmt::Debug`].
+/// [`Contextful`]. Can be formatted to show the chain with [`fmt::Debug`],
+/// which prints reports in the chain in full.
 #[derive(thiserror::Error, Clone)]
 #[error(transparent)]
 pub struct Error(#[doc(hidden)] pub Arc<anyhow::Error>);
 
 impl fmt::Debug for Error {
     fn fmt(&self, writer: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut chain = self.0.chain();
+        let mut cause = String::new();
 
-        if let Some(error) = chain.next() {
-            writeln!(writer, "{header} {error}", header = "error:".red().bold())?;
-        }
+        for (index, error) in self.0.chain().enumerate() {
+            // Reports have their own headers.
+            if let Some(report) = ReportContext::report_of(error) {
+                write!(writer, "{report}")?;
+                continue;
+            }
 
-        let mut cause = String::new();
+            if index == 0 {
+                writeln!(writer, "{header} {error}", header = "error:".red().bold())?;
+                continue;
+            }
 
-        for error in chain {
             write!(writer, "{header} ", header = "cause:".red().bold())?;
 
             cause.drain(..); // TODO: Replace with .clear() when yansi removes the method that shadows.
@@ -64,6 +72,42 @@ impl fmt::Debug for Error {
     }
 }
 
+/// A [`ReportDisplay`] in an error chain, caused by the error it was attached
+/// to, if any.
+struct ReportContext {
+    report: ReportDisplay,
+    source: Option<anyhow::Error>,
+}
+
+impl ReportContext {
+    /// Returns the report of the given error in a chain, looking through
+    /// [`Error`]s that wrap it.
+    fn report_of<'a>(error: &'a (dyn error::Error + 'static)) -> Option<&'a ReportDisplay> {
+        match error.downcast_ref::<Error>() {
+            Some(Error(error)) => Self::report_of(error.as_ref().as_ref()),
+            None => error.downcast_ref::<Self>().map(|context| &context.report),
+        }
+    }
+}
+
+impl fmt::Debug for ReportContext {
+    fn fmt(&self, writer: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, writer)
+    }
+}
+
+impl fmt::Display for ReportContext {
+    fn fmt(&self, writer: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(writer, "{report}", report = self.report)
+    }
+}
+
+impl error::Error for ReportContext {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source.as_deref().map(|error| error as _)
+    }
+}
+
 /// The termination type. Meant to be used as the return type of the main
 /// function.
 ///
@@ -168,6 +212,13 @@ pub trait Contextful<T> {
 
     /// Appends the context to the error chain, lazily.
     fn with_context<C: Context>(self, context: impl FnOnce() -> C) -> Result<T>;
+
+    /// Appends the report to the error chain. It is printed in full, with its
+    /// source snippets, when the error is.
+    fn report(self, report: ReportDisplay) -> Result<T>;
+
+    /// Appends the report to the error chain, lazily.
+    fn with_report(self, report: impl FnOnce() -> ReportDisplay) -> Result<T>;
 }
 
 impl<T> Contextful<T> for Option<T> {
@@ -178,6 +229,19 @@ impl<T> Contextful<T> for Option<T> {
     fn with_context<C: Context>(self, context: impl FnOnce() -> C) -> Result<T> {
         anyhow::Context::with_context(self, context).map_err(|error| Error(Arc::new(error)))
     }
+
+    fn report(self, report: ReportDisplay) -> Result<T> {
+        self.with_report(|| report)
+    }
+
+    fn with_report(self, report: impl FnOnce() -> ReportDisplay) -> Result<T> {
+        self.ok_or_else(|| {
+            Error(Arc::new(anyhow::Error::new(ReportContext {
+                report: report(),
+                source: None,
+            })))
+        })
+    }
 }
 
 impl<T, E: error::Error + Send + Sync + 'static> Contextful<T> for result::Result<T, E> {
@@ -188,4 +252,17 @@ impl<T, E: error::Error + Send + Sync + 'static> Contextful<T> for result::Resul
     fn with_context<C: Context>(self, context: impl FnOnce() -> C) -> Result<T> {
         anyhow::Context::with_context(self, context).map_err(|error| Error(Arc::new(error)))
     }
+
+    fn report(self, report: ReportDisplay) -> Result<T> {
+        self.with_report(|| report)
+    }
+
+    fn with_report(self, report: impl FnOnce() -> ReportDisplay) -> Result<T> {
+        self.map_err(|error| {
+            Error(Arc::new(anyhow::Error::new(ReportContext {
+                report: report(),
+                source: Some(anyhow::Error::new(error)),
+            })))
+        })
+    }
 }
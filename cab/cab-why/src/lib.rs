@@ -4,6 +4,7 @@
 
 #[cfg(feature = "error")]
 mod error;
+mod panic;
 mod print;
 mod report;
 mod text;
@@ -16,28 +17,56 @@ pub use self::error::{
     Result,
     Termination,
 };
+#[cfg(feature = "json")]
+pub use self::report::sarif_log;
 pub use self::{
+    panic::{
+        install_panic_hook,
+        processing,
+    },
     print::{
+        Document,
         IndentWith,
         IndentWriter,
         indent,
         indent_with,
         wrap,
+        wrap_at,
         wrapln,
+        wrapln_at,
     },
     report::{
+        Applicability,
+        Config,
+        Glyphs,
         Label,
         LabelSeverity,
         Point,
         Position,
+        Postprocessor,
         Report,
+        ReportDisplay,
         ReportSeverity,
+        ReportShort,
+        SeverityStyles,
+        Suggestion,
+        Theme,
     },
     text::{
+        Bias,
+        EditSet,
+        Encoding,
         IntoSize,
         IntoSpan,
+        LineColumn,
+        LineIndex,
+        OverlapError,
         Size,
+        Source,
+        SourceId,
+        SourceMap,
         Span,
+        TextEdit,
     },
 };
 
@@ -0,0 +1,68 @@
+use std::{
+    backtrace::Backtrace,
+    fmt,
+    io::{
+        self,
+        Write as _,
+    },
+    panic,
+    sync::{
+        Mutex,
+        PoisonError,
+    },
+};
+
+use yansi::Paint as _;
+
+use crate::{
+    Point,
+    Report,
+};
+
+static PROCESSING: Mutex<Option<String>> = Mutex::new(None);
+
+/// Sets the file that is being processed, which is displayed in the reports
+/// of panics. The previous one is restored when the returned guard is dropped.
+#[must_use = "the file is only displayed until the guard is dropped"]
+pub fn processing(file: impl fmt::Display) -> impl Drop {
+    let previous = PROCESSING
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .replace(file.to_string());
+
+    scopeguard::guard(previous, |previous| {
+        *PROCESSING.lock().unwrap_or_else(PoisonError::into_inner) = previous;
+    })
+}
+
+/// Installs a panic hook that writes panics to stderr as [`Report::bug`]s with
+/// the location of the panic, the file being processed and a backtrace,
+/// asking the user to report it at the given URL.
+pub fn install_panic_hook(url: &'static str) {
+    panic::set_hook(Box::new(move |info| {
+        let mut report = Report::bug(info.payload_as_str().unwrap_or("panicked").to_owned());
+
+        if let Some(location) = info.location() {
+            report.push_point(Point::new("location:".blue().bold(), location.to_string()));
+        }
+
+        if let Some(file) = &*PROCESSING.lock().unwrap_or_else(PoisonError::into_inner) {
+            report.push_point(Point::new("processing:".blue().bold(), file.clone()));
+        }
+
+        report.push_help(format!(
+            "this is a bug, please report it at {url} along with the backtrace below"
+        ));
+
+        let mut err = io::stderr().lock();
+
+        write!(err, "{report}", report = report.with("", "")).ok();
+        writeln!(
+            err,
+            "{header}\n{backtrace}",
+            header = "backtrace:".blue().bold(),
+            backtrace = Backtrace::force_capture(),
+        )
+        .ok();
+    }));
+}
@@ -0,0 +1,302 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    sync::atomic,
+};
+
+use unicode_width::UnicodeWidthStr as _;
+use yansi::Paint as _;
+
+use crate::text::{
+    LINE_WIDTH,
+    LINE_WIDTH_MAX,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+type Command<'a> = (u16, Mode, &'a Document);
+
+/// A document that is laid out at a width, choosing between the flat and
+/// broken layouts of its groups. Based on Wadler's "A prettier printer".
+///
+/// # Example
+///
+/// ```rs
+/// // Renders as `[ 1, 2, 3 ]` if it fits, and as one item per line otherwise.
+/// let items = (1..=3).map(|item| Document::text(item.to_string()));
+///
+/// let document = Document::text("[")
+///     .append(Document::line().append(Document::join(items, Document::text(",").append(Document::line()))).nest(2))
+///     .append(Document::line())
+///     .append(Document::text("]"))
+///     .group();
+/// ```
+#[derive(Debug, Clone)]
+pub enum Document {
+    /// Styled text without newlines.
+    Text(yansi::Painted<Cow<'static, str>>),
+    /// A line break, or the given text if its group is laid out flat.
+    Line(Cow<'static, str>),
+    /// A line break that is never laid out flat. Breaks every group that
+    /// contains it.
+    HardLine,
+    /// Indents the line breaks inside by the given amount.
+    Nest(u16, Box<Document>),
+    /// Lays out the document inside flat if it fits in the rest of the line,
+    /// and broken otherwise.
+    Group(Box<Document>),
+    /// Documents laid out one after the other.
+    Concat(Vec<Document>),
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, writer: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.render(writer, *LINE_WIDTH_MAX)
+    }
+}
+
+impl Document {
+    /// Creates an empty [`Document`].
+    pub fn empty() -> Self {
+        Self::Concat(Vec::new())
+    }
+
+    /// Creates an unstyled text [`Document`]. The text must not contain
+    /// newlines, use [`Document::hard_line`] instead.
+    pub fn text(text: impl Into<Cow<'static, str>>) -> Self {
+        Self::painted(text.into().new())
+    }
+
+    /// Creates a styled text [`Document`]. The text must not contain newlines,
+    /// use [`Document::hard_line`] instead.
+    pub fn painted(text: yansi::Painted<impl Into<Cow<'static, str>>>) -> Self {
+        let value = text.value.into();
+        debug_assert!(!value.contains('\n'), "text documents must not contain newlines");
+
+        Self::Text(yansi::Painted {
+            value,
+            style: text.style,
+        })
+    }
+
+    /// Creates a line break that is laid out as a space when flat.
+    pub fn line() -> Self {
+        Self::Line(Cow::Borrowed(" "))
+    }
+
+    /// Creates a line break that is laid out as nothing when flat.
+    pub fn soft_line() -> Self {
+        Self::Line(Cow::Borrowed(""))
+    }
+
+    /// Creates a line break that is never laid out flat.
+    pub fn hard_line() -> Self {
+        Self::HardLine
+    }
+
+    /// Concatenates the given documents.
+    pub fn concat(documents: impl IntoIterator<Item = Document>) -> Self {
+        Self::Concat(documents.into_iter().collect())
+    }
+
+    /// Concatenates the given documents with the separator between each.
+    pub fn join(documents: impl IntoIterator<Item = Document>, separator: Document) -> Self {
+        Self::Concat(documents.into_iter().intersperse(separator).collect())
+    }
+
+    /// Appends the given document to this one.
+    pub fn append(self, that: Document) -> Self {
+        match self {
+            Self::Concat(mut documents) => {
+                documents.push(that);
+                Self::Concat(documents)
+            },
+
+            this => Self::Concat(vec![this, that]),
+        }
+    }
+
+    /// Indents the line breaks in this document by the given amount.
+    pub fn nest(self, indent: u16) -> Self {
+        Self::Nest(indent, Box::new(self))
+    }
+
+    /// Groups this document, so it is laid out flat if it fits.
+    pub fn group(self) -> Self {
+        Self::Group(Box::new(self))
+    }
+
+    /// Lays out this document at the given width and writes it into the
+    /// writer. The current line is assumed to be empty, apart from the indent
+    /// of the enclosing [`IndentWriter`]s.
+    ///
+    /// [`IndentWriter`]: crate::IndentWriter
+    pub fn render(&self, writer: &mut dyn fmt::Write, width: u16) -> fmt::Result {
+        let column_start = LINE_WIDTH.load(atomic::Ordering::Acquire) as usize;
+        let mut column = column_start;
+
+        // Indents are only written before text, to avoid trailing whitespace.
+        let mut indent_pending = 0;
+
+        let mut stack: Vec<Command> = vec![(0, Mode::Break, self)];
+
+        while let Some((indent, mode, document)) = stack.pop() {
+            match document {
+                Self::Text(text) if text.value.is_empty() => {},
+
+                Self::Text(text) => {
+                    write!(writer, "{:indent_pending$}{text}", "")?;
+                    indent_pending = 0;
+
+                    column += text.value.width();
+                },
+
+                Self::Line(flat) if mode == Mode::Flat => {
+                    if !flat.is_empty() {
+                        write!(writer, "{:indent_pending$}{flat}", "")?;
+                        indent_pending = 0;
+                    }
+
+                    column += flat.width();
+                },
+
+                Self::Line(_) | Self::HardLine => {
+                    writeln!(writer)?;
+                    indent_pending = indent as usize;
+
+                    column = column_start + indent as usize;
+                },
+
+                Self::Nest(by, document) => stack.push((indent + by, mode, document)),
+
+                Self::Group(document) => {
+                    let remaining = width as isize - column as isize;
+
+                    let mode = if mode == Mode::Flat || fits(remaining, (indent, Mode::Flat, document), &stack) {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+
+                    stack.push((indent, mode, document));
+                },
+
+                Self::Concat(documents) => {
+                    stack.extend(documents.iter().rev().map(|document| (indent, mode, document)));
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether if the command fits in the remaining width, followed by the rest of
+/// the commands up to their first line break.
+fn fits(mut remaining: isize, command: Command<'_>, rest: &[Command<'_>]) -> bool {
+    let mut stack = vec![command];
+    let mut rest = rest.iter().rev().copied();
+
+    loop {
+        if remaining < 0 {
+            return false;
+        }
+
+        let Some((indent, mode, document)) = stack.pop().or_else(|| rest.next()) else {
+            return true;
+        };
+
+        match document {
+            Document::Text(text) => remaining -= text.value.width() as isize,
+
+            Document::Line(flat) if mode == Mode::Flat => remaining -= flat.width() as isize,
+            Document::Line(_) => return true,
+
+            Document::HardLine => return mode == Mode::Break,
+
+            Document::Nest(by, document) => stack.push((indent + by, mode, document)),
+
+            Document::Group(document) => stack.push((indent, mode, document)),
+
+            Document::Concat(documents) => {
+                stack.extend(documents.iter().rev().map(|document| (indent, mode, document)));
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(items: impl IntoIterator<Item = &'static str>) -> Document {
+        let items = items.into_iter().map(Document::text);
+
+        Document::text("[")
+            .append(
+                Document::line()
+                    .append(Document::join(items, Document::text(",").append(Document::line())))
+                    .nest(2),
+            )
+            .append(Document::line())
+            .append(Document::text("]"))
+            .group()
+    }
+
+    fn render(document: &Document, width: u16) -> String {
+        let mut string = String::new();
+        document.render(&mut string, width).unwrap();
+        string
+    }
+
+    #[test]
+    fn flat() {
+        assert_eq!(render(&list(["foo", "bar"]), 80), "[ foo, bar ]");
+    }
+
+    #[test]
+    fn broken() {
+        assert_eq!(render(&list(["foo", "bar"]), 10), "[\n  foo,\n  bar\n]");
+    }
+
+    #[test]
+    fn nested() {
+        let document = Document::text("[")
+            .append(Document::line().append(list(["foo", "bar"])).nest(2))
+            .append(Document::line())
+            .append(Document::text("]"))
+            .group();
+
+        assert_eq!(render(&document, 15), "[\n  [ foo, bar ]\n]");
+    }
+
+    #[test]
+    fn hard_line() {
+        let document = Document::text("foo")
+            .append(Document::hard_line())
+            .append(Document::hard_line())
+            .append(Document::text("bar"))
+            .nest(2)
+            .group();
+
+        assert_eq!(render(&document, 80), "foo\n\n  bar");
+    }
+
+    #[test]
+    fn wide() {
+        let wide = "x".repeat(usize::from(u16::MAX) + 1);
+        let document = Document::text(wide.clone()).append(list(["foo", "bar"]));
+
+        assert_eq!(render(&document, 80), format!("{wide}[\n  foo,\n  bar\n]"));
+    }
+}
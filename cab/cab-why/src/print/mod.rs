@@ -1,7 +1,9 @@
+mod document;
 mod indent;
 mod wrap;
 
 pub use self::{
+    document::Document,
     indent::{
         IndentPlace,
         IndentWith,
@@ -11,6 +13,8 @@ pub use self::{
     },
     wrap::{
         wrap,
+        wrap_at,
         wrapln,
+        wrapln_at,
     },
 };
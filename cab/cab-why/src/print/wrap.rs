@@ -20,13 +20,32 @@ pub fn wrapln<'a>(
     writer: &mut dyn fmt::Write,
     parts: impl IntoIterator<Item = yansi::Painted<&'a str>>,
 ) -> fmt::Result {
-    wrap(writer, parts)?;
-    writeln!(writer)
+    wrapln_at(writer, *LINE_WIDTH_MAX, parts)
 }
 
 /// Writes the given iterator of colored words into the writer, splicing and
 /// wrapping at the max line width.
 pub fn wrap<'a>(writer: &mut dyn fmt::Write, parts: impl IntoIterator<Item = yansi::Painted<&'a str>>) -> fmt::Result {
+    wrap_at(writer, *LINE_WIDTH_MAX, parts)
+}
+
+/// [`wrap_at`], but with a newline after the text.
+pub fn wrapln_at<'a>(
+    writer: &mut dyn fmt::Write,
+    width: u16,
+    parts: impl IntoIterator<Item = yansi::Painted<&'a str>>,
+) -> fmt::Result {
+    wrap_at(writer, width, parts)?;
+    writeln!(writer)
+}
+
+/// [`wrap`], but wrapping at the given line width instead of the width of the
+/// terminal.
+pub fn wrap_at<'a>(
+    writer: &mut dyn fmt::Write,
+    width: u16,
+    parts: impl IntoIterator<Item = yansi::Painted<&'a str>>,
+) -> fmt::Result {
     use None as Space;
     use Some as Word;
 
@@ -35,8 +54,8 @@ pub fn wrap<'a>(writer: &mut dyn fmt::Write, parts: impl IntoIterator<Item = yan
     let line_width_start = LINE_WIDTH.load(atomic::Ordering::Acquire);
     let mut line_width = line_width_start;
 
-    let line_width_max = if line_width_start < *LINE_WIDTH_MAX {
-        *LINE_WIDTH_MAX
+    let line_width_max = if line_width_start < width {
+        width
     } else {
         // If we can't even write any text just assume the line is uncapped.
         u16::MAX
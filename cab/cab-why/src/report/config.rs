@@ -0,0 +1,199 @@
+use crate::{
+    LabelSeverity,
+    ReportSeverity,
+    text::LINE_WIDTH_MAX,
+};
+
+/// The glyphs used to draw the gutter, strikes and label pointers of a
+/// [`ReportDisplay`].
+///
+/// [`ReportDisplay`]: crate::ReportDisplay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyphs {
+    pub right_to_bottom: char,
+    pub top_to_bottom: char,
+    pub top_to_bottom_partial: char,
+    pub dot: char,
+    pub top_to_right: char,
+    pub left_to_right: char,
+    pub left_to_top_bottom: char,
+
+    pub top_to_bottom_left: char,
+    pub top_left_to_right: char,
+    pub top_to_bottom_right: char,
+}
+
+impl Glyphs {
+    /// ASCII glyphs, for terminals and logs that mangle Unicode.
+    pub const ASCII: Self = Self {
+        right_to_bottom: ',',
+        top_to_bottom: '|',
+        top_to_bottom_partial: ':',
+        dot: '.',
+        top_to_right: '`',
+        left_to_right: '-',
+        left_to_top_bottom: '+',
+
+        top_to_bottom_left: '|',
+        top_left_to_right: '\\',
+        top_to_bottom_right: '|',
+    };
+    /// Unicode box-drawing glyphs.
+    pub const UNICODE: Self = Self {
+        right_to_bottom: '┏',
+        top_to_bottom: '┃',
+        top_to_bottom_partial: '┇',
+        dot: '·',
+        top_to_right: '┗',
+        left_to_right: '━',
+        left_to_top_bottom: '┫',
+
+        top_to_bottom_left: '▏',
+        top_left_to_right: '╲',
+        top_to_bottom_right: '▕',
+    };
+}
+
+/// The styles of a [`ReportSeverity`], for its primary and secondary labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeverityStyles {
+    pub primary: yansi::Style,
+    pub secondary: yansi::Style,
+}
+
+impl SeverityStyles {
+    const fn new(primary: yansi::Style, secondary: yansi::Style) -> Self {
+        Self { primary, secondary }
+    }
+}
+
+/// The color theme of a [`ReportDisplay`].
+///
+/// [`ReportDisplay`]: crate::ReportDisplay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub note: SeverityStyles,
+    pub warn: SeverityStyles,
+    pub error: SeverityStyles,
+    pub bug: SeverityStyles,
+
+    pub title: yansi::Style,
+
+    pub gutter: yansi::Style,
+    pub header_path: yansi::Style,
+    pub header_position: yansi::Style,
+
+    pub suggestion: yansi::Style,
+    pub diff_removed: yansi::Style,
+    pub diff_added: yansi::Style,
+
+    /// Overrides the styles of the titles of points, if set.
+    pub point: Option<yansi::Style>,
+}
+
+impl Theme {
+    /// The default theme, for dark terminal backgrounds.
+    pub const DARK: Self = Self {
+        note: SeverityStyles::new(yansi::Style::new().magenta(), yansi::Style::new().blue()),
+        warn: SeverityStyles::new(yansi::Style::new().yellow(), yansi::Style::new().blue()),
+        error: SeverityStyles::new(yansi::Style::new().red(), yansi::Style::new().yellow()),
+        bug: SeverityStyles::new(yansi::Style::new().red(), yansi::Style::new().yellow()),
+
+        title: yansi::Style::new().bold(),
+
+        gutter: yansi::Style::new().blue(),
+        header_path: yansi::Style::new().green(),
+        header_position: yansi::Style::new().blue(),
+
+        suggestion: yansi::Style::new().cyan().bold(),
+        diff_removed: yansi::Style::new().red(),
+        diff_added: yansi::Style::new().green(),
+
+        point: None,
+    };
+    /// A theme for light terminal backgrounds, avoiding yellow and cyan.
+    pub const LIGHT: Self = Self {
+        note: SeverityStyles::new(yansi::Style::new().magenta(), yansi::Style::new().blue()),
+        warn: SeverityStyles::new(yansi::Style::new().fixed(130), yansi::Style::new().blue()),
+        error: SeverityStyles::new(yansi::Style::new().red(), yansi::Style::new().fixed(130)),
+        bug: SeverityStyles::new(yansi::Style::new().red(), yansi::Style::new().fixed(130)),
+
+        title: yansi::Style::new().bold(),
+
+        gutter: yansi::Style::new().blue(),
+        header_path: yansi::Style::new().green(),
+        header_position: yansi::Style::new().blue(),
+
+        suggestion: yansi::Style::new().blue().bold(),
+        diff_removed: yansi::Style::new().red(),
+        diff_added: yansi::Style::new().green(),
+
+        point: None,
+    };
+    /// A theme without colors. Primary labels are bold instead.
+    pub const MONOCHROME: Self = Self {
+        note: SeverityStyles::new(yansi::Style::new().bold(), yansi::Style::new()),
+        warn: SeverityStyles::new(yansi::Style::new().bold(), yansi::Style::new()),
+        error: SeverityStyles::new(yansi::Style::new().bold(), yansi::Style::new()),
+        bug: SeverityStyles::new(yansi::Style::new().bold(), yansi::Style::new()),
+
+        title: yansi::Style::new().bold(),
+
+        gutter: yansi::Style::new(),
+        header_path: yansi::Style::new(),
+        header_position: yansi::Style::new(),
+
+        suggestion: yansi::Style::new().bold(),
+        diff_removed: yansi::Style::new(),
+        diff_added: yansi::Style::new(),
+
+        point: Some(yansi::Style::new().bold()),
+    };
+
+    /// Returns the style of a label with the given severity in a report with
+    /// the given severity.
+    pub fn label(&self, report: ReportSeverity, label: LabelSeverity) -> yansi::Style {
+        let styles = match report {
+            ReportSeverity::Note => self.note,
+            ReportSeverity::Warn => self.warn,
+            ReportSeverity::Error => self.error,
+            ReportSeverity::Bug => self.bug,
+        };
+
+        match label {
+            LabelSeverity::Primary => styles.primary,
+            LabelSeverity::Secondary => styles.secondary,
+        }
+    }
+}
+
+/// The configuration of a [`ReportDisplay`].
+///
+/// [`ReportDisplay`]: crate::ReportDisplay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub glyphs: Glyphs,
+    pub theme: Theme,
+    /// The width to wrap at. If not set, the width of the terminal is used.
+    pub width: Option<u16>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Config {
+    /// Unicode glyphs with the dark theme, wrapping at the terminal width.
+    pub const DEFAULT: Self = Self {
+        glyphs: Glyphs::UNICODE,
+        theme: Theme::DARK,
+        width: None,
+    };
+
+    /// Returns the width to wrap at.
+    pub fn width(&self) -> u16 {
+        self.width.unwrap_or(*LINE_WIDTH_MAX)
+    }
+}
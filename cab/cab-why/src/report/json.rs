@@ -0,0 +1,276 @@
+use std::{
+    env,
+    path::{
+        self,
+        Component,
+        Path,
+    },
+};
+
+use serde_json::{
+    Value,
+    json,
+};
+
+use crate::{
+    Applicability,
+    Label,
+    LabelSeverity,
+    LineIndex,
+    Position,
+    Report,
+    ReportSeverity,
+    Span,
+};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+fn position_json(position: Position) -> Value {
+    json!({
+        "line": position.line,
+        "column": position.column,
+    })
+}
+
+fn sarif_region(span: Span, index: &LineIndex) -> Value {
+    let (start, end) = index.position(span);
+
+    // SARIF end columns point to the character after the region.
+    json!({
+        "startLine": start.line,
+        "startColumn": start.column,
+        "endLine": end.line,
+        "endColumn": end.column + 1,
+    })
+}
+
+/// Returns the given local path as a URI reference relative to the working
+/// directory, which is what code scanning tools resolve against the root of
+/// the repository. Paths outside of the working directory are returned as
+/// `file://` URLs.
+fn uri(path: &Path) -> Option<String> {
+    let path = path::absolute(path).ok()?;
+
+    let Some(relative) = env::current_dir()
+        .ok()
+        .and_then(|directory| path.strip_prefix(directory).ok())
+    else {
+        return Some(format!("file://{path}", path = super::percent_encode(path.to_str()?)));
+    };
+
+    let parts = relative
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(super::percent_encode(&parts.join("/")))
+}
+
+fn sarif_location(uri: Option<&str>, span: Span, index: &LineIndex, text: &str) -> Value {
+    let mut physical = json!({ "region": sarif_region(span, index) });
+
+    if let Some(uri) = uri {
+        physical["artifactLocation"] = json!({ "uri": uri });
+    }
+
+    json!({
+        "physicalLocation": physical,
+        "message": { "text": text },
+    })
+}
+
+impl Report {
+    /// Serializes this report to a JSON object, resolving the positions of
+    /// spans with the [`LineIndex`] of the source, which is at the given local
+    /// path. The location of the report is the path relative to the working
+    /// directory, and is omitted if there is no path. Sources of labels and
+    /// suggestions are ignored.
+    ///
+    /// Meant to be written as a single line, as in JSON lines.
+    pub fn json(&self, path: Option<&Path>, index: &LineIndex) -> Value {
+        let location = path.and_then(uri);
+
+        let label_json = |label: &Label| {
+            let (start, end) = index.position(label.span);
+
+            json!({
+                "severity": match label.severity {
+                    LabelSeverity::Primary => "primary",
+                    LabelSeverity::Secondary => "secondary",
+                },
+                "text": label.text,
+                "span": { "start": *label.span.start, "end": *label.span.end },
+                "start": position_json(start),
+                "end": position_json(end),
+            })
+        };
+
+        let mut json = json!({
+            "severity": match self.severity {
+                ReportSeverity::Note => "note",
+                ReportSeverity::Warn => "warn",
+                ReportSeverity::Error => "error",
+                ReportSeverity::Bug => "bug",
+            },
+            "code": self.code,
+            "title": self.title,
+            "labels": self.labels.iter().map(label_json).collect::<Vec<_>>(),
+            "points": self.points.iter().map(|point| {
+                json!({
+                    "title": point.title.value.trim_end_matches(':'),
+                    "text": point.text,
+                })
+            }).collect::<Vec<_>>(),
+            "suggestions": self.suggestions.iter().map(|suggestion| {
+                json!({
+                    "text": suggestion.text,
+                    "span": { "start": *suggestion.span.start, "end": *suggestion.span.end },
+                    "replacement": suggestion.replacement,
+                    "applicability": match suggestion.applicability {
+                        Applicability::MachineApplicable => "machine-applicable",
+                        Applicability::MaybeIncorrect => "maybe-incorrect",
+                        Applicability::HasPlaceholders => "has-placeholders",
+                        Applicability::Unspecified => "unspecified",
+                    },
+                })
+            }).collect::<Vec<_>>(),
+            "children": self.children.iter().map(|child| child.json(path, index)).collect::<Vec<_>>(),
+        });
+
+        if let Some(location) = location {
+            json["location"] = json!(location);
+        }
+
+        json
+    }
+
+    /// Serializes this report to a SARIF result object, resolving the positions
+    /// of spans with the [`LineIndex`] of the source, which is at the given
+    /// local path. The artifact locations are the path relative to the working
+    /// directory, and are omitted if there is no path. Sources of labels and
+    /// suggestions are ignored.
+    ///
+    /// Primary labels become locations and secondary labels become related
+    /// locations, as do the labels of children, prefixed with their titles.
+    /// Collect the results in a log using [`sarif_log`].
+    pub fn sarif(&self, path: Option<&Path>, index: &LineIndex) -> Value {
+        let uri = path.and_then(uri);
+
+        let labels_of = |severity| {
+            self.labels
+                .iter()
+                .filter(move |label| label.severity == severity)
+                .map(|label| sarif_location(uri.as_deref(), label.span, index, &label.text))
+                .collect::<Vec<_>>()
+        };
+
+        let mut related = labels_of(LabelSeverity::Secondary);
+        let mut children: Vec<_> = self.children.iter().collect();
+        while let Some(child) = children.pop() {
+            related.extend(child.labels.iter().map(|label| {
+                let text = format!("{title}: {text}", title = child.title, text = label.text);
+                sarif_location(uri.as_deref(), label.span, index, &text)
+            }));
+
+            children.extend(&child.children);
+        }
+
+        let mut message = self.title.to_string();
+        for point in &self.points {
+            message.push_str("\n\n");
+            message.push_str(&point.title.value);
+            message.push(' ');
+            message.push_str(&point.text);
+        }
+
+        let mut json = json!({
+            "level": match self.severity {
+                ReportSeverity::Note => "note",
+                ReportSeverity::Warn => "warning",
+                ReportSeverity::Error | ReportSeverity::Bug => "error",
+            },
+            "message": { "text": message },
+            "locations": labels_of(LabelSeverity::Primary),
+            "relatedLocations": related,
+        });
+
+        if let Some(code) = &self.code {
+            json["ruleId"] = json!(code);
+        }
+
+        // Fixes can't be located without an artifact.
+        if let Some(uri) = uri {
+            json["fixes"] = self
+                .suggestions
+                .iter()
+                .map(|suggestion| {
+                    json!({
+                        "description": { "text": suggestion.text },
+                        "artifactChanges": [{
+                            "artifactLocation": { "uri": uri },
+                            "replacements": [{
+                                "deletedRegion": sarif_region(suggestion.span, index),
+                                "insertedContent": { "text": suggestion.replacement },
+                            }],
+                        }],
+                    })
+                })
+                .collect();
+        }
+
+        json
+    }
+}
+
+/// Collects SARIF results created by [`Report::sarif`] into a SARIF log with a
+/// single run of the given tool.
+pub fn sarif_log(tool: &str, version: &str, results: impl IntoIterator<Item = Value>) -> Value {
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": tool,
+                    "version": version,
+                },
+            },
+            "results": results.into_iter().collect::<Vec<_>>(),
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sarif() {
+        let report = Report::error("foo").primary(Span::new(0u32, 3u32), "bar");
+        let path = env::current_dir().unwrap().join("src").join("foo bar.cab");
+
+        let index = LineIndex::new("foo");
+
+        let sarif = report.sarif(Some(&path), &index);
+        assert_eq!(sarif.get("ruleId"), None);
+        assert_eq!(
+            sarif["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "src/foo%20bar.cab",
+        );
+
+        let sarif = report.code("E0001").sarif(None, &index);
+        assert_eq!(sarif["ruleId"], "E0001");
+        assert_eq!(sarif["locations"][0]["physicalLocation"].get("artifactLocation"), None);
+    }
+
+    #[test]
+    fn json() {
+        let report = Report::error("foo");
+        let index = LineIndex::new("");
+
+        assert_eq!(report.json(Some(Path::new("./foo.cab")), &index)["location"], "foo.cab");
+        assert_eq!(report.json(None, &index).get("location"), None);
+    }
+}
@@ -2,7 +2,9 @@ use std::borrow::Cow;
 
 use crate::{
     ReportSeverity,
+    SourceId,
     Span,
+    Theme,
     into,
 };
 
@@ -15,25 +17,9 @@ pub enum LabelSeverity {
 
 impl LabelSeverity {
     /// Returns the applicable style of this label severity in the given report
-    /// severity.
+    /// severity, using the default [`Theme`].
     pub fn style_in(self, severity: ReportSeverity) -> yansi::Style {
-        use LabelSeverity::*;
-        use ReportSeverity::*;
-
-        match (severity, self) {
-            (Note, Secondary) => yansi::Color::Blue,
-            (Note, Primary) => yansi::Color::Magenta,
-
-            (Warn, Secondary) => yansi::Color::Blue,
-            (Warn, Primary) => yansi::Color::Yellow,
-
-            (Error, Secondary) => yansi::Color::Yellow,
-            (Error, Primary) => yansi::Color::Red,
-
-            (Bug, Secondary) => yansi::Color::Yellow,
-            (Bug, Primary) => yansi::Color::Red,
-        }
-        .foreground()
+        Theme::DARK.label(severity, self)
     }
 }
 
@@ -42,6 +28,9 @@ impl LabelSeverity {
 pub struct Label {
     /// The span.
     pub span: Span,
+    /// The source the span is in. Labels without a source are in the source
+    /// the report is displayed with.
+    pub source: Option<SourceId>,
     /// The label severity.
     pub severity: LabelSeverity,
     /// The text that will be displayed at the end of the label.
@@ -54,7 +43,12 @@ impl Label {
     pub fn new(span: impl Into<Span>, text: impl Into<Cow<'static, str>>, severity: LabelSeverity) -> Self {
         into!(span, text);
 
-        Self { span, text, severity }
+        Self {
+            span,
+            source: None,
+            text,
+            severity,
+        }
     }
 
     /// Creates a new primary [`Label`].
@@ -68,4 +62,11 @@ impl Label {
     pub fn secondary(span: impl Into<Span>, text: impl Into<Cow<'static, str>>) -> Self {
         Self::new(span, text, LabelSeverity::Secondary)
     }
+
+    /// Sets the source of the span of this label.
+    #[inline]
+    pub fn in_source(mut self, source: SourceId) -> Self {
+        self.source = Some(source);
+        self
+    }
 }
@@ -1,6 +1,12 @@
+mod config;
+#[cfg(feature = "json")]
+mod json;
 mod label;
 mod point;
 mod position;
+mod process;
+mod short;
+mod suggestion;
 
 use std::{
     borrow::Cow,
@@ -12,28 +18,53 @@ use std::{
         Write as _,
     },
     iter,
+    path::{
+        self,
+        Path,
+        PathBuf,
+    },
 };
 
 use smallvec::SmallVec;
 use unicode_segmentation::UnicodeSegmentation;
-use yansi::Paint;
+use yansi::{
+    Paint,
+    hyperlink::HyperlinkExt as _,
+};
 
+#[cfg(feature = "json")]
+pub use self::json::sarif_log;
 pub use self::{
+    config::{
+        Config,
+        Glyphs,
+        SeverityStyles,
+        Theme,
+    },
     label::{
         Label,
         LabelSeverity,
     },
     point::Point,
     position::Position,
+    process::Postprocessor,
+    short::ReportShort,
+    suggestion::{
+        Applicability,
+        Suggestion,
+    },
 };
 use crate::{
     IntoSize,
+    LineIndex,
     Size,
+    SourceId,
+    SourceMap,
     Span,
     dedent,
     indent,
     into,
-    wrapln,
+    wrapln_at,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -46,13 +77,38 @@ pub enum ReportSeverity {
 
 impl ReportSeverity {
     pub fn header(self) -> yansi::Painted<&'static str> {
+        self.header_in(&Theme::DARK)
+    }
+
+    /// Returns the header of this severity with the given code, such as
+    /// `error[E0012]:`.
+    pub fn header_with(self, code: Option<&str>, theme: &Theme) -> yansi::Painted<Cow<'static, str>> {
+        let header = self.header_in(theme);
+
+        let value = match code {
+            Some(code) => {
+                Cow::Owned(format!(
+                    "{severity}[{code}]:",
+                    severity = header.value.trim_end_matches(':')
+                ))
+            },
+            None => Cow::Borrowed(header.value),
+        };
+
+        yansi::Painted {
+            value,
+            style: header.style,
+        }
+    }
+
+    pub fn header_in(self, theme: &Theme) -> yansi::Painted<&'static str> {
         match self {
             ReportSeverity::Note => "note:",
             ReportSeverity::Warn => "warn:",
             ReportSeverity::Error => "error:",
             ReportSeverity::Bug => "bug:",
         }
-        .paint(LabelSeverity::Primary.style_in(self))
+        .paint(theme.label(self, LabelSeverity::Primary))
         .bold()
     }
 }
@@ -60,9 +116,12 @@ impl ReportSeverity {
 #[derive(Debug, Clone)]
 pub struct Report {
     pub severity: ReportSeverity,
+    pub code: Option<Cow<'static, str>>,
     pub title: Cow<'static, str>,
     pub labels: SmallVec<Label, 2>,
     pub points: SmallVec<Point, 2>,
+    pub suggestions: SmallVec<Suggestion, 1>,
+    pub children: Vec<Report>,
 }
 
 impl Report {
@@ -72,8 +131,11 @@ impl Report {
         Self {
             title,
             severity,
+            code: None,
             labels: SmallVec::new(),
             points: SmallVec::new(),
+            suggestions: SmallVec::new(),
+            children: Vec::new(),
         }
     }
 
@@ -93,8 +155,14 @@ impl Report {
         Self::new(ReportSeverity::Bug, title)
     }
 
+    /// Sets the stable code of this report, such as `E0012`.
+    pub fn code(mut self, code: impl Into<Cow<'static, str>>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.labels.is_empty() && self.points.is_empty()
+        self.labels.is_empty() && self.points.is_empty() && self.suggestions.is_empty() && self.children.is_empty()
     }
 
     pub fn push_label(&mut self, label: Label) {
@@ -119,8 +187,12 @@ impl Report {
         self
     }
 
-    pub fn point(mut self, point: Point) -> Self {
+    pub fn push_point(&mut self, point: Point) {
         self.points.push(point);
+    }
+
+    pub fn point(mut self, point: Point) -> Self {
+        self.push_point(point);
         self
     }
 
@@ -140,8 +212,80 @@ impl Report {
         self.point(Point::help(text))
     }
 
-    pub fn with<Location: fmt::Display>(self, location: Location, source: &str) -> ReportDisplay<Location> {
-        ReportDisplay::from(self, source, location)
+    pub fn push_suggestion(&mut self, suggestion: Suggestion) {
+        self.suggestions.push(suggestion);
+    }
+
+    pub fn suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.push_suggestion(suggestion);
+        self
+    }
+
+    /// Adds a child report, which is displayed indented beneath this one. Its
+    /// labels may be in other sources.
+    pub fn push_child(&mut self, child: Report) {
+        self.children.push(child);
+    }
+
+    pub fn child(mut self, child: Report) -> Self {
+        self.push_child(child);
+        self
+    }
+
+    /// Displays this report with the given source, which is displayed as the
+    /// given location. Sources of labels and suggestions are ignored.
+    pub fn with(self, location: impl fmt::Display, source: &str) -> ReportDisplay {
+        self.with_index(location, source, &LineIndex::new(source))
+    }
+
+    /// Displays this report like [`Report::with`], but with the already built
+    /// [`LineIndex`] of the source, for when it is shared by multiple reports.
+    pub fn with_index(self, location: impl fmt::Display, source: &str, index: &LineIndex) -> ReportDisplay {
+        let files = [(location.to_string(), source, None, index)];
+
+        ReportDisplay::from(self, &files, &|_| 0)
+    }
+
+    fn collect_sources(&self, ids: &mut SmallVec<SourceId, 2>) {
+        let labels = self.labels.iter().map(|label| label.source);
+        let suggestions = self.suggestions.iter().map(|suggestion| suggestion.source);
+
+        for source in labels.chain(suggestions) {
+            if let Some(id) = source
+                && !ids.contains(&id)
+            {
+                ids.push(id);
+            }
+        }
+
+        for child in &self.children {
+            child.collect_sources(ids);
+        }
+    }
+
+    /// Displays this report with the sources in the given [`SourceMap`]. Labels
+    /// and suggestions without a source are displayed in the given source.
+    pub fn with_sources(self, sources: &SourceMap, source: SourceId) -> ReportDisplay {
+        let mut ids = SmallVec::<SourceId, 2>::from_iter([source]);
+        self.collect_sources(&mut ids);
+
+        let files: SmallVec<_, 2> = ids
+            .iter()
+            .map(|&id| {
+                let source = &sources[id];
+                (
+                    source.name().to_owned(),
+                    source.content(),
+                    source.path(),
+                    source.index(),
+                )
+            })
+            .collect();
+
+        ReportDisplay::from(self, &files, &|id| {
+            let id = id.unwrap_or(source);
+            ids.iter().position(|&other| other == id).unwrap()
+        })
     }
 }
 
@@ -153,6 +297,30 @@ fn number_width(number: u32) -> usize {
     }
 }
 
+/// Percent-encodes every byte of the given path except unreserved characters
+/// and `/`, so it can be used in a URL.
+fn percent_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => encoded.push(byte as char),
+            _ => write!(encoded, "%{byte:02X}").expect("writing to a string must not fail"),
+        }
+    }
+
+    encoded
+}
+
+/// Returns the `file://` URL of the given absolute path, with the line and
+/// column numbers as the fragment, like in the header.
+fn file_url(path: &Path, line: u32, column: u32) -> Option<String> {
+    Some(format!(
+        "file://{path}#{line}:{column}",
+        path = percent_encode(path.to_str()?)
+    ))
+}
+
 fn extend_to_line_boundaries(source: &str, mut span: Span) -> Span {
     while *span.start > 0
         && source
@@ -177,6 +345,7 @@ fn resolve_style(
     content: &str,
     mut styles: SmallVec<LineStyle, 4>,
     severity: ReportSeverity,
+    theme: Theme,
 ) -> impl Iterator<Item = yansi::Painted<&str>> + '_ {
     styles.sort_by(|a_style, b_style| {
         match (
@@ -223,18 +392,18 @@ fn resolve_style(
                             style_offset += style_offset_diff;
 
                             yield content[Span::std(content_offset, contained_style.span.start)]
-                                .paint(style.severity.style_in(severity));
+                                .paint(theme.label(severity, style.severity));
 
                             yield content[contained_style.span.as_std()]
-                                .paint(contained_style.severity.style_in(severity));
+                                .paint(theme.label(severity, contained_style.severity));
 
                             yield content[Span::std(contained_style.span.end, style.span.end)]
-                                .paint(style.severity.style_in(severity));
+                                .paint(theme.label(severity, style.severity));
                         },
 
                         None => {
                             yield content[Span::std(content_offset, style.span.end)]
-                                .paint(style.severity.style_in(severity));
+                                .paint(theme.label(severity, style.severity));
                         },
                     }
 
@@ -329,44 +498,78 @@ struct Line {
     labels: SmallVec<LineLabel, 2>,
 }
 
+#[derive(Debug, Clone)]
+struct File {
+    location: String,
+    path: Option<PathBuf>,
+
+    lines: SmallVec<Line, 10>,
+}
+
+#[derive(Debug, Clone)]
+struct Diff {
+    text: Cow<'static, str>,
+
+    number: u32,
+
+    removed: String,
+    added: String,
+}
+
 #[derive(Clone)]
-pub struct ReportDisplay<Location: fmt::Display> {
+pub struct ReportDisplay {
     severity: ReportSeverity,
+    code: Option<Cow<'static, str>>,
     title: Cow<'static, str>,
 
-    location: Location,
+    files: SmallVec<File, 1>,
 
-    lines: SmallVec<Line, 10>,
+    diffs: SmallVec<Diff, 1>,
 
     points: SmallVec<Point, 2>,
+
+    children: Vec<ReportDisplay>,
+
+    config: Config,
 }
 
-const RIGHT_TO_BOTTOM: char = '┏';
-const TOP_TO_BOTTOM: char = '┃';
-const TOP_TO_BOTTOM_PARTIAL: char = '┇';
-const DOT: char = '·';
-const TOP_TO_RIGHT: char = '┗';
-const LEFT_TO_RIGHT: char = '━';
-const LEFT_TO_TOP_BOTTOM: char = '┫';
+impl fmt::Display for ReportDisplay {
+    fn fmt(&self, writer: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_report(writer)?;
 
-const TOP_TO_BOTTOM_LEFT: char = '▏';
-const TOP_LEFT_TO_RIGHT: char = '╲';
-const TOP_TO_BOTTOM_RIGHT: char = '▕';
+        for child in &self.children {
+            // INDENT: "    "
+            indent!(writer, 4);
 
-const STYLE_GUTTER: yansi::Style = yansi::Style::new().blue();
-const STYLE_HEADER_PATH: yansi::Style = yansi::Style::new().green();
-const STYLE_HEADER_POSITION: yansi::Style = yansi::Style::new().blue();
+            write!(writer, "{child}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ReportDisplay {
+    fn write_report(&self, writer: &mut dyn fmt::Write) -> fmt::Result {
+        let Config { glyphs, theme, .. } = &self.config;
+        let width = self.config.width();
 
-impl<Location: fmt::Display> fmt::Display for ReportDisplay<Location> {
-    fn fmt(&self, writer: &mut fmt::Formatter<'_>) -> fmt::Result {
         {
-            // INDENT: "<note|warn|error|bug>: "
-            indent!(writer, header = self.severity.header());
+            // INDENT: "<note|warn|error|bug>[<code>]: "
+            let header = self.severity.header_with(self.code.as_deref(), theme);
+            indent!(writer, header = &header);
 
-            wrapln(writer, [self.title.as_ref().bold()])?;
+            wrapln_at(writer, width, [self.title.as_ref().paint(theme.title)])?;
         }
 
-        let line_number_width = self.lines.last().map_or(0, |line| number_width(line.number));
+        let line_number_width = self
+            .files
+            .iter()
+            .filter_map(|file| file.lines.last())
+            .map(|line| line.number)
+            .chain(self.diffs.iter().map(Diff::last_number))
+            .map(number_width)
+            .max()
+            .unwrap_or(0);
 
         // INDENT: "123 | "
         let line_number = RefCell::new(None::<u32>);
@@ -382,7 +585,7 @@ impl<Location: fmt::Display> fmt::Display for ReportDisplay<Location> {
 
                 let mut line_number_previous = line_number_previous.borrow_mut();
 
-                STYLE_GUTTER.fmt_prefix(writer)?;
+                theme.gutter.fmt_prefix(writer)?;
                 match () {
                     // Don't write the current line number, just print spaces instead.
                     _ if !*line_number_should_write.borrow() => {
@@ -397,7 +600,7 @@ impl<Location: fmt::Display> fmt::Display for ReportDisplay<Location> {
                         write!(writer, "{:>space_width$}", "")?;
 
                         for _ in 0..dot_width {
-                            write!(writer, "{DOT}")?;
+                            write!(writer, "{dot}", dot = glyphs.dot)?;
                         }
                     },
 
@@ -405,7 +608,12 @@ impl<Location: fmt::Display> fmt::Display for ReportDisplay<Location> {
                     _ if line_number_previous
                         .is_some_and(|line_number_previous| line_number > line_number_previous + 1) =>
                     {
-                        writeln!(writer, "{:>line_number_width$} {TOP_TO_BOTTOM_PARTIAL} ", "")?;
+                        writeln!(
+                            writer,
+                            "{:>line_number_width$} {partial} ",
+                            "",
+                            partial = glyphs.top_to_bottom_partial
+                        )?;
                         write!(writer, "{line_number:>line_number_width$}")?;
                     },
 
@@ -415,362 +623,431 @@ impl<Location: fmt::Display> fmt::Display for ReportDisplay<Location> {
                     },
                 }
 
-                write!(writer, " {TOP_TO_BOTTOM} ")?;
-                STYLE_GUTTER.fmt_suffix(writer)?;
+                write!(writer, " {top_to_bottom} ", top_to_bottom = glyphs.top_to_bottom)?;
+                theme.gutter.fmt_suffix(writer)?;
 
                 line_number_previous.replace(line_number);
                 Ok(line_number_width + 3)
             }
         );
 
-        if let Some(line) = self.lines.first() {
-            // DEDENT: "| "
-            dedent!(writer, 2);
+        for file in &self.files {
+            if let Some(line) = file.lines.first() {
+                // DEDENT: "| "
+                dedent!(writer, 2);
 
-            // INDENT: "┏━━━ ".
-            indent!(
-                writer,
-                header = const_str::concat!(RIGHT_TO_BOTTOM, LEFT_TO_RIGHT, LEFT_TO_RIGHT, LEFT_TO_RIGHT)
-                    .paint(STYLE_GUTTER)
-            );
-
-            STYLE_HEADER_PATH.fmt_prefix(writer)?;
-            write!(writer, "{location}", location = self.location)?;
-            STYLE_HEADER_PATH.fmt_suffix(writer)?;
-
-            let line_number = line.number.paint(STYLE_HEADER_POSITION);
-            let column_number = *line.styles.first().unwrap().span.start + 1;
-            let column_number = column_number.paint(STYLE_HEADER_POSITION);
-            writeln!(writer, ":{line_number}:{column_number}")?;
-        }
+                // INDENT: "┏━━━ ".
+                let header = String::from_iter([
+                    glyphs.right_to_bottom,
+                    glyphs.left_to_right,
+                    glyphs.left_to_right,
+                    glyphs.left_to_right,
+                ]);
+                indent!(writer, header = header.as_str().paint(theme.gutter));
 
-        let strike_prefix_width = self.lines.iter().map(|line| line.strikes.len()).max().unwrap_or(0);
+                let location = file.location.as_str().paint(theme.header_path);
+                let column = *line.styles.first().unwrap().span.start + 1;
 
-        {
-            // INDENT: "<strike-prefix> "
-            let strike_prefix = RefCell::new(SmallVec::<_, 3>::from_iter(iter::repeat_n(
-                None::<LineStrike>,
-                strike_prefix_width,
-            )));
-            indent!(
-                writer,
-                strike_prefix_width + 1,
-                with = |writer: &mut dyn fmt::Write| {
-                    const STRIKE_OVERRIDE_DEFAULT: yansi::Painted<&char> = yansi::Painted::new(&' ');
-
-                    let mut strike_override = None::<yansi::Painted<&char>>;
-
-                    for slot in &*strike_prefix.borrow() {
-                        let Some(strike) = *slot else {
-                            write!(
-                                writer,
-                                "{symbol}",
-                                symbol = strike_override.unwrap_or(STRIKE_OVERRIDE_DEFAULT)
-                            )?;
-                            continue;
-                        };
-
-                        match strike.status {
-                            LineStrikeStatus::Start => {
-                                write!(
-                                    writer,
-                                    "{symbol}",
-                                    symbol = RIGHT_TO_BOTTOM.paint(self.style(strike.severity))
-                                )?;
+                let line_number = line.number.paint(theme.header_position);
+                let column_number = column.paint(theme.header_position);
 
-                                strike_override = Some(LEFT_TO_RIGHT.paint(self.style(strike.severity)));
-                            },
+                let header = format!("{location}:{line_number}:{column_number}");
 
-                            LineStrikeStatus::Continue | LineStrikeStatus::End
-                                if let Some(strike) = strike_override =>
-                            {
-                                write!(writer, "{strike}")?;
-                            },
+                match file
+                    .path
+                    .as_deref()
+                    .and_then(|path| file_url(path, line.number, column))
+                {
+                    Some(url) => writeln!(writer, "{header}", header = header.link(url))?,
+                    None => writeln!(writer, "{header}")?,
+                }
+            }
+
+            let strike_prefix_width = file.lines.iter().map(|line| line.strikes.len()).max().unwrap_or(0);
 
-                            LineStrikeStatus::Continue | LineStrikeStatus::End => {
+            {
+                // INDENT: "<strike-prefix> "
+                let strike_prefix = RefCell::new(SmallVec::<_, 3>::from_iter(iter::repeat_n(
+                    None::<LineStrike>,
+                    strike_prefix_width,
+                )));
+                indent!(
+                    writer,
+                    strike_prefix_width + 1,
+                    with = |writer: &mut dyn fmt::Write| {
+                        const STRIKE_OVERRIDE_DEFAULT: yansi::Painted<&char> = yansi::Painted::new(&' ');
+
+                        let mut strike_override = None::<yansi::Painted<&char>>;
+
+                        for slot in &*strike_prefix.borrow() {
+                            let Some(strike) = *slot else {
                                 write!(
                                     writer,
                                     "{symbol}",
-                                    symbol = TOP_TO_BOTTOM.paint(self.style(strike.severity))
+                                    symbol = strike_override.unwrap_or(STRIKE_OVERRIDE_DEFAULT)
                                 )?;
-                            },
+                                continue;
+                            };
+
+                            match strike.status {
+                                LineStrikeStatus::Start => {
+                                    write!(
+                                        writer,
+                                        "{symbol}",
+                                        symbol = glyphs.right_to_bottom.paint(self.style(strike.severity))
+                                    )?;
+
+                                    strike_override = Some(glyphs.left_to_right.paint(self.style(strike.severity)));
+                                },
+
+                                LineStrikeStatus::Continue | LineStrikeStatus::End
+                                    if let Some(strike) = strike_override =>
+                                {
+                                    write!(writer, "{strike}")?;
+                                },
+
+                                LineStrikeStatus::Continue | LineStrikeStatus::End => {
+                                    write!(
+                                        writer,
+                                        "{symbol}",
+                                        symbol = glyphs.top_to_bottom.paint(self.style(strike.severity))
+                                    )?;
+                                },
+                            }
                         }
-                    }
 
-                    write!(
-                        writer,
-                        "{symbol}",
-                        symbol = strike_override.unwrap_or(STRIKE_OVERRIDE_DEFAULT)
-                    )?;
+                        write!(
+                            writer,
+                            "{symbol}",
+                            symbol = strike_override.unwrap_or(STRIKE_OVERRIDE_DEFAULT)
+                        )?;
 
-                    Ok(strike_prefix_width + 1)
-                }
-            );
+                        Ok(strike_prefix_width + 1)
+                    }
+                );
 
-            for (line_index, line) in self.lines.iter().enumerate() {
-                line_number.borrow_mut().replace(line.number);
+                for (line_index, line) in file.lines.iter().enumerate() {
+                    line_number.borrow_mut().replace(line.number);
 
-                // Write an empty line at the start.
-                if line_index == 0 {
-                    *line_number_should_write.borrow_mut() = false;
+                    // Write an empty line at the start.
+                    if line_index == 0 {
+                        *line_number_should_write.borrow_mut() = false;
 
-                    writer.write_indent()?;
-                    writeln!(writer)?;
+                        writer.write_indent()?;
+                        writeln!(writer)?;
 
-                    *line_number_previous.borrow_mut() = None;
-                }
+                        *line_number_previous.borrow_mut() = None;
+                    }
 
-                // Patch strike prefix and keep track of positions of strikes with their IDs.
-                {
-                    let mut strike_prefix = strike_prefix.borrow_mut();
-
-                    for strike_new @ LineStrike { id, .. } in line.strikes.iter().copied() {
-                        match strike_prefix.iter_mut().flatten().find(|strike| strike.id == id) {
-                            Some(strike) => *strike = strike_new,
-
-                            None => {
-                                strike_prefix
-                                    .iter_mut()
-                                    .find(|slot| slot.is_none())
-                                    .unwrap()
-                                    .replace(strike_new);
-                            },
+                    // Patch strike prefix and keep track of positions of strikes with their IDs.
+                    {
+                        let mut strike_prefix = strike_prefix.borrow_mut();
+
+                        for strike_new @ LineStrike { id, .. } in line.strikes.iter().copied() {
+                            match strike_prefix.iter_mut().flatten().find(|strike| strike.id == id) {
+                                Some(strike) => *strike = strike_new,
+
+                                None => {
+                                    strike_prefix
+                                        .iter_mut()
+                                        .find(|slot| slot.is_none())
+                                        .unwrap()
+                                        .replace(strike_new);
+                                },
+                            }
                         }
                     }
-                }
 
-                // Write the line.
-                {
-                    *line_number_should_write.borrow_mut() = true;
+                    // Write the line.
+                    {
+                        *line_number_should_write.borrow_mut() = true;
 
-                    // Explicitly write the indent because the line may be empty.
-                    writer.write_indent()?;
-                    wrapln(writer, resolve_style(&line.content, line.styles.clone(), self.severity))?;
+                        // Explicitly write the indent because the line may be empty.
+                        writer.write_indent()?;
+                        wrapln_at(
+                            writer,
+                            width,
+                            resolve_style(&line.content, line.styles.clone(), self.severity, *theme),
+                        )?;
 
-                    *line_number_should_write.borrow_mut() = false;
-                }
+                        *line_number_should_write.borrow_mut() = false;
+                    }
 
-                // Write the line labels.
-                // Reverse, because we want to print the labels that end the last first.
-                for (label_index, label) in line.labels.iter().enumerate().rev() {
-                    // HACK: wrapln may split the current line into multiple
-                    // lines, so the label pointer may be too far left.
-                    // Just max it to 60 for now.
-                    let span_start = label.span.start().min(Some(60u32.into()));
-                    let span_end = label.span.end().min(60u32.into());
-
-                    // DEDENT: "<strike-prefix> "
-                    dedent!(writer);
-
-                    match label.span {
-                        LineLabelSpan::UpTo(_) => {
-                            let (top_to_right_index, top_to_right) = strike_prefix
-                                .borrow()
-                                .iter()
-                                .enumerate()
-                                .rev()
-                                .find_map(|(index, strike)| {
-                                    match strike {
-                                        Some(strike) if strike.status == LineStrikeStatus::End => {
-                                            Some((index, *strike))
-                                        },
-
-                                        _ => None,
-                                    }
-                                })
-                                .unwrap();
-
-                            assert_eq!(top_to_right.severity, label.severity);
-
-                            // INDENT: "<strike-prefix>"
-                            let mut wrote = false;
-                            indent!(
-                                writer,
-                                strike_prefix_width,
-                                with = |writer: &mut dyn fmt::Write| {
-                                    // Write all strikes up to the index of the one we are going to redirect to the
-                                    // right.
-                                    for slot in strike_prefix.borrow().iter().take(top_to_right_index) {
-                                        write!(
-                                            writer,
-                                            "{symbol}",
-                                            symbol = match slot {
-                                                Some(strike) => TOP_TO_BOTTOM.paint(self.style(strike.severity)),
-                                                None => (&' ').new(),
-                                            }
-                                        )?;
-                                    }
+                    // Write the line labels.
+                    // Reverse, because we want to print the labels that end the last first.
+                    for (label_index, label) in line.labels.iter().enumerate().rev() {
+                        // HACK: wrapln may split the current line into multiple
+                        // lines, so the label pointer may be too far left.
+                        // Just max it to 60 for now.
+                        let span_start = label.span.start().min(Some(60u32.into()));
+                        let span_end = label.span.end().min(60u32.into());
+
+                        // DEDENT: "<strike-prefix> "
+                        dedent!(writer);
+
+                        match label.span {
+                            LineLabelSpan::UpTo(_) => {
+                                let (top_to_right_index, top_to_right) = strike_prefix
+                                    .borrow()
+                                    .iter()
+                                    .enumerate()
+                                    .rev()
+                                    .find_map(|(index, strike)| {
+                                        match strike {
+                                            Some(strike) if strike.status == LineStrikeStatus::End => {
+                                                Some((index, *strike))
+                                            },
+
+                                            _ => None,
+                                        }
+                                    })
+                                    .unwrap();
 
-                                    if wrote {
-                                        return Ok(top_to_right_index);
-                                    }
+                                assert_eq!(top_to_right.severity, label.severity);
 
-                                    write!(
-                                        writer,
-                                        "{symbol}",
-                                        symbol = TOP_TO_RIGHT.paint(self.style(top_to_right.severity))
-                                    )?;
+                                // INDENT: "<strike-prefix>"
+                                let mut wrote = false;
+                                indent!(
+                                    writer,
+                                    strike_prefix_width,
+                                    with = |writer: &mut dyn fmt::Write| {
+                                        // Write all strikes up to the index of the one we are going to redirect to the
+                                        // right.
+                                        for slot in strike_prefix.borrow().iter().take(top_to_right_index) {
+                                            write!(
+                                                writer,
+                                                "{symbol}",
+                                                symbol = match slot {
+                                                    Some(strike) =>
+                                                        glyphs.top_to_bottom.paint(self.style(strike.severity)),
+                                                    None => (&' ').new(),
+                                                }
+                                            )?;
+                                        }
+
+                                        if wrote {
+                                            return Ok(top_to_right_index);
+                                        }
 
-                                    for _ in 0..strike_prefix_width - top_to_right_index - 1 {
                                         write!(
                                             writer,
                                             "{symbol}",
-                                            symbol = LEFT_TO_RIGHT.paint(self.style(top_to_right.severity))
+                                            symbol = glyphs.top_to_right.paint(self.style(top_to_right.severity))
                                         )?;
+
+                                        for _ in 0..strike_prefix_width - top_to_right_index - 1 {
+                                            write!(
+                                                writer,
+                                                "{symbol}",
+                                                symbol = glyphs.left_to_right.paint(self.style(top_to_right.severity))
+                                            )?;
+                                        }
+
+                                        wrote = true;
+                                        Ok(strike_prefix_width)
                                     }
+                                );
+
+                                // INDENT: "<left-to-right><left-to-bottom>"
+                                // INDENT: "               <top--to-bottom>"
+                                let mut wrote = false;
+                                indent!(
+                                    writer,
+                                    // + 1 because the span is zero-indexed and we didn't indent the space after
+                                    //   <strike-prefix> before.
+                                    //
+                                    // + 1 because we want a space after the <top-to-bottom>.
+                                    *span_end + 2,
+                                    with = |writer: &mut dyn fmt::Write| {
+                                        for index in 0..*span_end {
+                                            write!(
+                                                writer,
+                                                "{symbol}",
+                                                symbol = match () {
+                                                    // If there is a label on the current line after this label that has
+                                                    // a start or end at the current index, write it instead of out
+                                                    // <left-to-right>
+                                                    _ if let Some(label) =
+                                                        line.labels[..label_index].iter().rev().find(|label| {
+                                                            *label.span.end() == index && !label.span.is_empty()
+                                                                || label
+                                                                    .span
+                                                                    .start()
+                                                                    .is_some_and(|start| *start + 1 == index)
+                                                        }) =>
+                                                    {
+                                                        if label.span.is_empty() {
+                                                            glyphs.top_to_bottom_left.paint(self.style(label.severity))
+                                                        } else {
+                                                            glyphs.top_to_bottom.paint(self.style(label.severity))
+                                                        }
+                                                    },
+
+                                                    _ if !wrote =>
+                                                        glyphs.left_to_right.paint(self.style(top_to_right.severity)),
+
+                                                    _ => (&' ').new(),
+                                                }
+                                            )?;
+                                        }
 
-                                    wrote = true;
-                                    Ok(strike_prefix_width)
-                                }
-                            );
-
-                            // INDENT: "<left-to-right><left-to-bottom>"
-                            // INDENT: "               <top--to-bottom>"
-                            let mut wrote = false;
-                            indent!(
-                                writer,
-                                // + 1 because the span is zero-indexed and we didn't indent the space after
-                                //   <strike-prefix> before.
-                                //
-                                // + 1 because we want a space after the <top-to-bottom>.
-                                *span_end + 2,
-                                with = |writer: &mut dyn fmt::Write| {
-                                    for index in 0..*span_end {
                                         write!(
                                             writer,
                                             "{symbol}",
                                             symbol = match () {
-                                                // If there is a label on the current line after this label that has a
-                                                // start or end at the
-                                                // current index, write it instead of out <left-to-right>
-                                                _ if let Some(label) =
-                                                    line.labels[..label_index].iter().rev().find(|label| {
-                                                        *label.span.end() == index && !label.span.is_empty()
-                                                            || label
-                                                                .span
-                                                                .start()
-                                                                .is_some_and(|start| *start + 1 == index)
-                                                    }) =>
-                                                {
-                                                    if label.span.is_empty() {
-                                                        TOP_TO_BOTTOM_LEFT.paint(self.style(label.severity))
-                                                    } else {
-                                                        TOP_TO_BOTTOM.paint(self.style(label.severity))
-                                                    }
-                                                },
-
-                                                _ if !wrote => LEFT_TO_RIGHT.paint(self.style(top_to_right.severity)),
-
-                                                _ => (&' ').new(),
+                                                _ if !wrote => glyphs.left_to_top_bottom,
+                                                _ => glyphs.top_to_bottom,
                                             }
+                                            .paint(self.style(top_to_right.severity))
                                         )?;
-                                    }
 
-                                    write!(
-                                        writer,
-                                        "{symbol}",
-                                        symbol = match () {
-                                            _ if !wrote => LEFT_TO_TOP_BOTTOM,
-                                            _ => TOP_TO_BOTTOM,
-                                        }
-                                        .paint(self.style(top_to_right.severity))
-                                    )?;
+                                        wrote = true;
+                                        strike_prefix.borrow_mut()[top_to_right_index] = None;
+                                        Ok(*span_end + 1)
+                                    }
+                                );
 
-                                    wrote = true;
-                                    strike_prefix.borrow_mut()[top_to_right_index] = None;
-                                    Ok(*span_end + 1)
-                                }
-                            );
+                                wrapln_at(
+                                    writer,
+                                    width,
+                                    [label.text.as_ref().paint(self.style(top_to_right.severity))],
+                                )?;
+                            },
 
-                            wrapln(writer, [label.text.as_ref().paint(self.style(top_to_right.severity))])?;
-                        },
+                            LineLabelSpan::Inline(_) => {
+                                let span_start = span_start.unwrap();
 
-                        LineLabelSpan::Inline(_) => {
-                            let span_start = span_start.unwrap();
+                                // INDENT: "<strike-prefix> "
+                                indent!(
+                                    writer,
+                                    strike_prefix_width + 1,
+                                    with = |writer: &mut dyn fmt::Write| {
+                                        for slot in &*strike_prefix.borrow() {
+                                            write!(
+                                                writer,
+                                                "{symbol}",
+                                                symbol = match slot {
+                                                    Some(strike) =>
+                                                        glyphs.top_to_bottom.paint(self.style(strike.severity)),
+                                                    None => (&' ').new(),
+                                                }
+                                            )?;
+                                        }
 
-                            // INDENT: "<strike-prefix> "
-                            indent!(
-                                writer,
-                                strike_prefix_width + 1,
-                                with = |writer: &mut dyn fmt::Write| {
-                                    for slot in &*strike_prefix.borrow() {
-                                        write!(
-                                            writer,
-                                            "{symbol}",
-                                            symbol = match slot {
-                                                Some(strike) => TOP_TO_BOTTOM.paint(self.style(strike.severity)),
-                                                None => (&' ').new(),
-                                            }
-                                        )?;
+                                        Ok(strike_prefix_width)
                                     }
+                                );
+
+                                // INDENT: "               <top-to-right><left-to-right><left-to-bottom> "
+                                // INDENT: "                                            <top--to-bottom> "
+                                let mut wrote = false;
+                                indent!(
+                                    writer,
+                                    // + 1 for extra space.
+                                    // + 1 if the label is zero-width. The <top-left-to-right> will be placed after the
+                                    //   span.
+                                    *span_end + if span_start == span_end { 1 } else { 0 } + 1,
+                                    with = |writer: &mut dyn fmt::Write| {
+                                        for index in 0..*span_end - if span_start == span_end { 0 } else { 1 } {
+                                            write!(
+                                                writer,
+                                                "{symbol}",
+                                                symbol = match () {
+                                                    _ if index == *span_start =>
+                                                        glyphs.top_to_right.paint(self.style(label.severity)),
+
+                                                    _ if let Some(label) =
+                                                        line.labels[..label_index].iter().rev().find(|label| {
+                                                            *label.span.end() == index + 1 && !label.span.is_empty()
+                                                                || label
+                                                                    .span
+                                                                    .start()
+                                                                    .is_some_and(|start| *start == index)
+                                                        }) =>
+                                                    {
+                                                        if label.span.is_empty() {
+                                                            glyphs.top_to_bottom_left.paint(self.style(label.severity))
+                                                        } else {
+                                                            glyphs.top_to_bottom.paint(self.style(label.severity))
+                                                        }
+                                                    },
+
+                                                    _ if !wrote && index > *span_start => {
+                                                        glyphs.left_to_right.paint(self.style(label.severity))
+                                                    },
+
+                                                    _ => (&' ').new(),
+                                                }
+                                            )?;
+                                        }
 
-                                    Ok(strike_prefix_width)
-                                }
-                            );
-
-                            // INDENT: "               <top-to-right><left-to-right><left-to-bottom> "
-                            // INDENT: "                                            <top--to-bottom> "
-                            let mut wrote = false;
-                            indent!(
-                                writer,
-                                // + 1 for extra space.
-                                // + 1 if the label is zero-width. The <top-left-to-right> will be placed after the
-                                //   span.
-                                *span_end + if span_start == span_end { 1 } else { 0 } + 1,
-                                with = |writer: &mut dyn fmt::Write| {
-                                    for index in 0..*span_end - if span_start == span_end { 0 } else { 1 } {
                                         write!(
                                             writer,
                                             "{symbol}",
-                                            symbol = match () {
-                                                _ if index == *span_start =>
-                                                    TOP_TO_RIGHT.paint(self.style(label.severity)),
-
-                                                _ if let Some(label) =
-                                                    line.labels[..label_index].iter().rev().find(|label| {
-                                                        *label.span.end() == index + 1 && !label.span.is_empty()
-                                                            || label.span.start().is_some_and(|start| *start == index)
-                                                    }) =>
-                                                {
-                                                    if label.span.is_empty() {
-                                                        TOP_TO_BOTTOM_LEFT.paint(self.style(label.severity))
-                                                    } else {
-                                                        TOP_TO_BOTTOM.paint(self.style(label.severity))
-                                                    }
-                                                },
-
-                                                _ if !wrote && index > *span_start => {
-                                                    LEFT_TO_RIGHT.paint(self.style(label.severity))
-                                                },
-
-                                                _ => (&' ').new(),
+                                            symbol = match *span_end - *span_start {
+                                                0 if wrote => glyphs.top_to_bottom_right,
+                                                _ if wrote => glyphs.top_to_bottom,
+
+                                                0 => glyphs.top_left_to_right,
+                                                1 => glyphs.top_to_bottom,
+
+                                                _ => glyphs.left_to_top_bottom,
                                             }
+                                            .paint(self.style(label.severity))
                                         )?;
+
+                                        wrote = true;
+                                        Ok(*span_end + if span_start == span_end { 1 } else { 0 })
                                     }
+                                );
 
-                                    write!(
-                                        writer,
-                                        "{symbol}",
-                                        symbol = match *span_end - *span_start {
-                                            0 if wrote => TOP_TO_BOTTOM_RIGHT,
-                                            _ if wrote => TOP_TO_BOTTOM,
+                                wrapln_at(writer, width, [label.text.as_ref().paint(self.style(label.severity))])?;
+                            },
+                        }
+                    }
+                }
+            }
+        }
 
-                                            0 => TOP_LEFT_TO_RIGHT,
-                                            1 => TOP_TO_BOTTOM,
+        // Write the suggestions.
+        for diff in &self.diffs {
+            line_number.borrow_mut().replace(diff.number);
+            *line_number_should_write.borrow_mut() = false;
 
-                                            _ => LEFT_TO_TOP_BOTTOM,
-                                        }
-                                        .paint(self.style(label.severity))
-                                    )?;
+            writer.write_indent()?;
+            writeln!(writer)?;
 
-                                    wrote = true;
-                                    Ok(*span_end + if span_start == span_end { 1 } else { 0 })
-                                }
-                            );
+            {
+                // DEDENT: "| "
+                dedent!(writer, 2);
 
-                            wrapln(writer, [label.text.as_ref().paint(self.style(label.severity))])?;
-                        },
-                    }
+                // INDENT: "= "
+                indent!(writer, header = "=".paint(theme.gutter));
+
+                // INDENT: "help: "
+                indent!(writer, header = "help:".paint(theme.suggestion));
+
+                wrapln_at(writer, width, [diff.text.as_ref().new()])?;
+            }
+
+            writer.write_indent()?;
+            writeln!(writer)?;
+
+            *line_number_previous.borrow_mut() = None;
+
+            for (content, sign, style) in [
+                (&diff.removed, "- ", theme.diff_removed),
+                (&diff.added, "+ ", theme.diff_added),
+            ] {
+                for (number, line) in (diff.number..).zip(content.split('\n')) {
+                    line_number.borrow_mut().replace(number);
+                    *line_number_should_write.borrow_mut() = true;
+
+                    // Explicitly write the indent because the line may be empty.
+                    writer.write_indent()?;
+                    wrapln_at(writer, width, [sign.paint(style), line.paint(style)])?;
+
+                    *line_number_should_write.borrow_mut() = false;
                 }
             }
         }
@@ -787,12 +1064,13 @@ impl<Location: fmt::Display> fmt::Display for ReportDisplay<Location> {
 
             for point in &self.points {
                 // INDENT: "= "
-                indent!(writer, header = "=".paint(STYLE_GUTTER));
+                indent!(writer, header = "=".paint(theme.gutter));
 
                 // INDENT: "<tip|help|...>: "
-                indent!(writer, header = &point.title);
+                let title = point.title.value.as_ref();
+                indent!(writer, header = title.paint(theme.point.unwrap_or(point.title.style)));
 
-                wrapln(writer, [point.text.as_ref().new()])?;
+                wrapln_at(writer, width, [point.text.as_ref().new()])?;
             }
         }
 
@@ -800,20 +1078,94 @@ impl<Location: fmt::Display> fmt::Display for ReportDisplay<Location> {
     }
 }
 
-impl<Location: fmt::Display> fmt::Debug for ReportDisplay<Location> {
+impl fmt::Debug for ReportDisplay {
     fn fmt(&self, writer: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt::Display::fmt(self, writer)
     }
 }
 
-impl<Location: fmt::Display> error::Error for ReportDisplay<Location> {}
+impl error::Error for ReportDisplay {}
+
+impl ReportDisplay {
+    fn from(
+        report: Report,
+        files: &[(String, &str, Option<&Path>, &LineIndex)],
+        file_of: &dyn Fn(Option<SourceId>) -> usize,
+    ) -> Self {
+        let mut labels_of = SmallVec::<SmallVec<Label, 2>, 1>::from_iter(iter::repeat_n(SmallVec::new(), files.len()));
+
+        for label in report.labels {
+            labels_of[file_of(label.source)].push(label);
+        }
+
+        Self {
+            severity: report.severity,
+            code: report.code,
+            title: report.title,
 
-impl<Location: fmt::Display> ReportDisplay<Location> {
-    fn from(report: Report, source: &str, location: Location) -> Self {
-        let mut labels: SmallVec<_, 2> = report
-            .labels
+            files: files
+                .iter()
+                .zip(labels_of)
+                .map(|((location, source, path, index), labels)| {
+                    File {
+                        location: location.clone(),
+                        path: path.map(Path::to_owned),
+                        lines: Self::lines(labels, source, index),
+                    }
+                })
+                .collect(),
+
+            diffs: report
+                .suggestions
+                .into_iter()
+                .map(|suggestion| {
+                    let (_, source, _, index) = files[file_of(suggestion.source)];
+                    Diff::of(suggestion, source, index)
+                })
+                .collect(),
+
+            points: report.points,
+
+            children: report
+                .children
+                .into_iter()
+                .map(|child| Self::from(child, files, file_of))
+                .collect(),
+
+            config: Config::DEFAULT,
+        }
+    }
+
+    /// Sets the local filesystem path of the source this report is displayed
+    /// with, hyperlinking the header of its snippet. Relative paths are
+    /// resolved against the current directory.
+    ///
+    /// Hyperlinks are only written when painting is enabled.
+    pub fn path(self, path: impl AsRef<Path>) -> Self {
+        self.path_of(path::absolute(path).ok())
+    }
+
+    fn path_of(mut self, path: Option<PathBuf>) -> Self {
+        self.children = self
+            .children
+            .into_iter()
+            .map(|child| child.path_of(path.clone()))
+            .collect();
+        self.files[0].path = path;
+        self
+    }
+
+    /// Sets the configuration to render with, including the children.
+    pub fn config(mut self, config: Config) -> Self {
+        self.children = self.children.into_iter().map(|child| child.config(config)).collect();
+        self.config = config;
+        self
+    }
+
+    fn lines(labels: SmallVec<Label, 2>, source: &str, index: &LineIndex) -> SmallVec<Line, 10> {
+        let mut labels: SmallVec<_, 2> = labels
             .into_iter()
-            .map(|label| (Position::of(label.span, source), label))
+            .map(|label| (index.position(label.span), label))
             .collect();
 
         // Sort by line, and when labels are on the same line, sort by column. The one
@@ -956,19 +1308,117 @@ impl<Location: fmt::Display> ReportDisplay<Location> {
             });
         }
 
+        lines
+    }
+
+    fn style(&self, severity: LabelSeverity) -> yansi::Style {
+        self.config.theme.label(self.severity, severity)
+    }
+}
+
+impl Diff {
+    fn of(suggestion: Suggestion, source: &str, index: &LineIndex) -> Self {
+        let span_extended = extend_to_line_boundaries(source, suggestion.span);
+        let (start, _) = index.position(suggestion.span);
+
         Self {
-            severity: report.severity,
-            title: report.title,
+            number: start.line,
 
-            location,
+            removed: source[span_extended.as_std()].to_owned(),
+            added: [
+                &source[Span::std(span_extended.start, suggestion.span.start)],
+                suggestion.replacement.as_ref(),
+                &source[Span::std(suggestion.span.end, span_extended.end)],
+            ]
+            .concat(),
 
-            lines,
+            text: suggestion.text,
+        }
+    }
 
-            points: report.points,
+    fn last_number(&self) -> u32 {
+        let lines = self.removed.split('\n').count().max(self.added.split('\n').count());
+
+        self.number + lines as u32 - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the display of the report without escape sequences.
+    fn plain(report: ReportDisplay) -> String {
+        let display = report.to_string();
+
+        let mut plain = String::new();
+        let mut chars = display.chars();
+
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                plain.push(c);
+            }
         }
+
+        plain
     }
 
-    fn style(&self, severity: LabelSeverity) -> yansi::Style {
-        severity.style_in(self.severity)
+    #[test]
+    fn with_sources() {
+        let mut sources = SourceMap::new();
+
+        let a = sources.insert("<a>", "foo = bar;\n");
+        let b = sources.insert("<b>", "bar = 1;\n");
+
+        // Does not affect the labels of the old source.
+        sources.insert("<b>", "");
+
+        let mut child = Report::note("defined");
+        child.push_label(Label::primary(Span::new(0u32, 3u32), "defined here").in_source(b));
+
+        let report = Report::error("undefined")
+            .primary(Span::new(6u32, 9u32), "used here")
+            .child(child);
+
+        let display = plain(report.with_sources(&sources, a));
+
+        let a_header = display.find("<a>:1:7").expect("header of <a> must be displayed");
+        let b_header = display.find("<b>:1:1").expect("header of <b> must be displayed");
+        assert!(a_header < b_header);
+
+        assert!(display[a_header..b_header].contains("foo = bar;"));
+        assert!(display[a_header..b_header].contains("used here"));
+        assert!(display[b_header..].contains("bar = 1;"));
+        assert!(display[b_header..].contains("defined here"));
+    }
+
+    #[test]
+    fn hyperlink() {
+        let source = "foo = bar;\n";
+        let report = || Report::error("undefined").primary(Span::new(6u32, 9u32), "used here");
+
+        let path = path::absolute("foo bar.cab").unwrap();
+        let url = format!(
+            "\x1b]8;;file://{path}#1:7\x1b\\",
+            path = percent_encode(path.to_str().unwrap())
+        );
+        assert!(url.contains("foo%20bar.cab#1:7"));
+
+        let display = report().with("foo bar.cab", source).path(&path).to_string();
+        assert!(display.contains(&url));
+
+        // Locations that are not files are not hyperlinked.
+        let display = report().with("<stdin>", source).to_string();
+        assert!(!display.contains("\x1b]8;;"));
+
+        // Neither is output that is not painted, such as when it is not a TTY.
+        yansi::disable();
+        let display = report().with("foo bar.cab", source).path(&path).to_string();
+        yansi::enable();
+
+        assert!(!display.contains("\x1b]8;;"));
+        assert!(display.contains("foo bar.cab:1:7"));
     }
 }
@@ -1,4 +1,7 @@
-use crate::Span;
+use crate::{
+    LineIndex,
+    Span,
+};
 
 /// A position in a source file.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -15,41 +18,11 @@ pub struct Position {
 
 impl Position {
     /// Calculates the start and end position of the span in the given source.
+    ///
+    /// Indexes the whole source, so prefer [`LineIndex::position`] when
+    /// calculating more than one position in the same source.
     pub fn of(span: Span, source: &str) -> (Position, Position) {
-        let range: std::ops::Range<usize> = span.into();
-
-        let mut line = 1;
-        let mut column = 1;
-
-        let mut start = Position { line, column };
-        let mut end = Position { line, column };
-
-        let mut index = 0;
-
-        for c in source.chars() {
-            index += c.len_utf8();
-
-            if c == '\n' {
-                line += 1;
-                column = 0;
-            } else {
-                column += 1;
-            }
-
-            if index == range.start {
-                start.line = line;
-                start.column = column;
-            }
-
-            if index >= range.end {
-                end.line = line;
-                end.column = column;
-
-                break;
-            }
-        }
-
-        (start, end)
+        LineIndex::new(source).position(span)
     }
 }
 
@@ -87,5 +60,17 @@ mod tests {
             Position::of(Span::new(0u32, 7u32), source),
             (Position { line: 1, column: 1 }, Position { line: 2, column: 2 })
         );
+
+        let source = "foo\nbar";
+        assert_eq!(&source[1..3], "oo");
+        assert_eq!(
+            Position::of(Span::new(1u32, 3u32), source),
+            (Position { line: 1, column: 2 }, Position { line: 1, column: 3 })
+        );
+        assert_eq!(&source[5..7], "ar");
+        assert_eq!(
+            Position::of(Span::new(5u32, 7u32), source),
+            (Position { line: 2, column: 2 }, Position { line: 2, column: 3 })
+        );
     }
 }
@@ -0,0 +1,171 @@
+use crate::{
+    LabelSeverity,
+    Report,
+    ReportSeverity,
+    Span,
+};
+
+impl Report {
+    /// Returns the span that covers the primary labels of this report in the
+    /// source it is displayed with. Falls back to all of its labels in that
+    /// source if it has no primary ones.
+    pub fn span(&self) -> Option<Span> {
+        let labels = || self.labels.iter().filter(|label| label.source.is_none());
+
+        labels()
+            .filter(|label| label.severity == LabelSeverity::Primary)
+            .map(|label| label.span)
+            .reduce(Span::cover)
+            .or_else(|| labels().map(|label| label.span).reduce(Span::cover))
+    }
+}
+
+/// Post-processes a list of reports before they are displayed, to avoid
+/// flooding the user on badly broken sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Postprocessor {
+    /// The maximum amount of error and bug reports to keep. The rest are
+    /// omitted and summarized in a single note at the end.
+    pub max_errors: Option<usize>,
+}
+
+impl Postprocessor {
+    /// Creates a new [`Postprocessor`] that keeps every report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum amount of error and bug reports to keep.
+    pub fn max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = Some(max_errors);
+        self
+    }
+
+    /// Processes the given reports:
+    ///
+    /// 1. Sorts them by the start of their [`Report::span`]. Reports without
+    ///    spans are moved to the end. Reports that start at the same position
+    ///    keep their order.
+    ///
+    /// 2. Merges error and bug reports that have the same key as an earlier one
+    ///    into it as children, as they are most likely cascaded from it.
+    ///    Reports with a key of [`None`] are never merged.
+    ///
+    /// 3. Omits the error and bug reports past the maximum, appending a note
+    ///    with the amount of omitted ones.
+    pub fn process<K: PartialEq>(
+        self,
+        mut reports: Vec<Report>,
+        mut key_of: impl FnMut(&Report) -> Option<K>,
+    ) -> Vec<Report> {
+        reports.sort_by_key(|report| {
+            let span = report.span();
+            (span.is_none(), span.map(|span| span.start))
+        });
+
+        let mut merged = Vec::<(Option<K>, Report)>::with_capacity(reports.len());
+
+        for report in reports {
+            let key = key_of(&report);
+
+            if key.is_some()
+                && report.severity >= ReportSeverity::Error
+                && let Some((_, parent)) = merged
+                    .iter_mut()
+                    .find(|(other, parent)| *other == key && parent.severity >= ReportSeverity::Error)
+            {
+                parent.push_child(report);
+                continue;
+            }
+
+            merged.push((key, report));
+        }
+
+        let mut errors = 0;
+        let mut omitted = 0;
+
+        let mut reports: Vec<_> = merged
+            .into_iter()
+            .map(|(_, report)| report)
+            .filter(|report| {
+                if report.severity < ReportSeverity::Error {
+                    return true;
+                }
+
+                errors += 1;
+
+                let keep = self.max_errors.is_none_or(|max_errors| errors <= max_errors);
+                if !keep {
+                    omitted += 1;
+                }
+
+                keep
+            })
+            .collect();
+
+        if omitted > 0 {
+            reports.push(Report::note(format!(
+                "{omitted} more {errors} omitted",
+                errors = if omitted == 1 { "error" } else { "errors" },
+            )));
+        }
+
+        reports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(reports: &[Report]) -> Vec<&str> {
+        reports.iter().map(|report| report.title.as_ref()).collect()
+    }
+
+    #[test]
+    fn merge() {
+        let reports = Postprocessor::new().process(
+            vec![
+                Report::error("cascaded").primary(Span::new(5u32, 6u32), ""),
+                Report::error("first").primary(Span::new(4u32, 8u32), ""),
+                Report::warn("warning").primary(Span::new(4u32, 6u32), ""),
+                Report::error("unkeyed").primary(Span::new(6u32, 7u32), ""),
+                Report::error("other").primary(Span::new(10u32, 11u32), ""),
+            ],
+            |report| {
+                match report.span()?.start {
+                    start if *start >= 10 => Some(1),
+                    start if *start == 6 => None,
+                    _ => Some(0),
+                }
+            },
+        );
+
+        assert_eq!(titles(&reports), ["first", "warning", "unkeyed", "other"]);
+        assert_eq!(titles(&reports[0].children), ["cascaded"]);
+        assert!(reports[1..].iter().all(|report| report.children.is_empty()));
+    }
+
+    #[test]
+    fn max_errors() {
+        let reports = Postprocessor::new().max_errors(2).process(
+            vec![
+                Report::error("a").primary(Span::new(0u32, 1u32), ""),
+                Report::warn("b").primary(Span::new(1u32, 2u32), ""),
+                Report::bug("c").primary(Span::new(2u32, 3u32), ""),
+                Report::error("d").primary(Span::new(3u32, 4u32), ""),
+                Report::error("e"),
+            ],
+            |_| None::<()>,
+        );
+
+        assert_eq!(titles(&reports), ["a", "b", "c", "2 more errors omitted"]);
+        assert_eq!(reports[3].severity, ReportSeverity::Note);
+
+        let reports = Postprocessor::new()
+            .max_errors(2)
+            .process(vec![Report::error("a"), Report::error("b")], |_| None::<()>);
+
+        assert_eq!(titles(&reports), ["a", "b"]);
+    }
+}
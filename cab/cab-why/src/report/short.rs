@@ -0,0 +1,99 @@
+use std::{
+    borrow::Cow,
+    fmt,
+};
+
+use smallvec::SmallVec;
+
+use crate::{
+    Config,
+    LineIndex,
+    Position,
+    Report,
+    ReportSeverity,
+};
+
+/// A compact display of a [`Report`], writing a single line per label in the
+/// form of `location:line:column: severity: title: label`.
+///
+/// Children are written after their parent in the same form. Suitable for
+/// grepping and quickfix lists. Created using [`Report::short`].
+#[derive(Debug, Clone)]
+pub struct ReportShort {
+    severity: ReportSeverity,
+    code: Option<Cow<'static, str>>,
+    title: Cow<'static, str>,
+
+    location: String,
+
+    labels: SmallVec<(Position, Cow<'static, str>), 2>,
+
+    children: Vec<ReportShort>,
+
+    config: Config,
+}
+
+impl fmt::Display for ReportShort {
+    fn fmt(&self, writer: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = &self.location;
+        let severity = self.severity.header_with(self.code.as_deref(), &self.config.theme);
+        let title = &self.title;
+
+        if self.labels.is_empty() {
+            writeln!(writer, "{location}: {severity} {title}")?;
+        }
+
+        for (Position { line, column }, text) in &self.labels {
+            writeln!(writer, "{location}:{line}:{column}: {severity} {title}: {text}")?;
+        }
+
+        for child in &self.children {
+            write!(writer, "{child}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Report {
+    /// Displays this report compactly with the [`LineIndex`] of the source it
+    /// is in, which is displayed as the given location. Sources of labels are
+    /// ignored.
+    pub fn short(self, location: impl fmt::Display, index: &LineIndex) -> ReportShort {
+        let location = location.to_string();
+
+        let children = self
+            .children
+            .into_iter()
+            .map(|child| child.short(&location, index))
+            .collect();
+
+        ReportShort {
+            severity: self.severity,
+            code: self.code,
+            title: self.title,
+
+            location,
+
+            labels: self
+                .labels
+                .into_iter()
+                .map(|label| (index.position(label.span).0, label.text))
+                .collect(),
+
+            children,
+
+            config: Config::DEFAULT,
+        }
+    }
+}
+
+impl ReportShort {
+    /// Sets the configuration to render with, including the children. Only the
+    /// theme is used, as there are no snippets to draw.
+    pub fn config(mut self, config: Config) -> Self {
+        self.children = self.children.into_iter().map(|child| child.config(config)).collect();
+        self.config = config;
+        self
+    }
+}
@@ -0,0 +1,65 @@
+use std::borrow::Cow;
+
+use crate::{
+    SourceId,
+    Span,
+    into,
+};
+
+/// How confident a [`Suggestion`] is that it is what the user intended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Applicability {
+    /// The suggestion is definitely what the user intended. It can be applied
+    /// automatically.
+    MachineApplicable,
+    /// The suggestion may be what the user intended, but it is not certain.
+    MaybeIncorrect,
+    /// The suggestion contains placeholders that the user has to fill in.
+    HasPlaceholders,
+    /// The applicability of the suggestion is unknown.
+    Unspecified,
+}
+
+/// A suggestion to replace a span with some text.
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    /// The span to replace.
+    pub span: Span,
+    /// The source the span is in. Suggestions without a source are in the
+    /// source the report is displayed with.
+    pub source: Option<SourceId>,
+    /// The text to replace the span with.
+    pub replacement: Cow<'static, str>,
+    /// The text that describes the suggestion.
+    pub text: Cow<'static, str>,
+    /// The applicability of the suggestion.
+    pub applicability: Applicability,
+}
+
+impl Suggestion {
+    /// Creates a new [`Suggestion`].
+    #[inline]
+    pub fn new(
+        span: impl Into<Span>,
+        replacement: impl Into<Cow<'static, str>>,
+        text: impl Into<Cow<'static, str>>,
+        applicability: Applicability,
+    ) -> Self {
+        into!(span, replacement, text);
+
+        Self {
+            span,
+            source: None,
+            replacement,
+            text,
+            applicability,
+        }
+    }
+
+    /// Sets the source of the span of this suggestion.
+    #[inline]
+    pub fn in_source(mut self, source: SourceId) -> Self {
+        self.source = Some(source);
+        self
+    }
+}
@@ -0,0 +1,299 @@
+use std::{
+    borrow::Cow,
+    error,
+    fmt,
+};
+
+use crate::{
+    Size,
+    Span,
+};
+
+/// The side a position sticks to when the text around it is replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bias {
+    /// Sticks to the start of the replacement.
+    Left,
+    /// Sticks to the end of the replacement.
+    Right,
+}
+
+/// An edit that replaces a span of a source with a replacement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The span to replace.
+    pub span: Span,
+    /// The text to replace the span with.
+    pub replacement: Cow<'static, str>,
+}
+
+impl TextEdit {
+    /// Creates a new [`TextEdit`] that replaces the span with the replacement.
+    pub fn replace(span: impl Into<Span>, replacement: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            span: span.into(),
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Creates a new [`TextEdit`] that inserts the text at the given offset.
+    pub fn insert(at: impl Into<Size>, text: impl Into<Cow<'static, str>>) -> Self {
+        Self::replace(Span::empty(at), text)
+    }
+
+    /// Creates a new [`TextEdit`] that deletes the span.
+    pub fn delete(span: impl Into<Span>) -> Self {
+        Self::replace(span, "")
+    }
+
+    /// Returns the span the replacement occupies after this edit is applied.
+    pub fn new_span(&self) -> Span {
+        Span::at(self.span.start, self.replacement.len())
+    }
+
+    /// Returns the amount the offsets after this edit are moved by.
+    pub fn delta(&self) -> i64 {
+        self.replacement.len() as i64 - i64::from(*self.span.len())
+    }
+}
+
+/// An error that is returned when creating an [`EditSet`] with edits that
+/// overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverlapError {
+    /// The span of the first edit.
+    pub first: Span,
+    /// The span of the second edit, which overlaps the first one.
+    pub second: Span,
+}
+
+impl fmt::Display for OverlapError {
+    fn fmt(&self, writer: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            writer,
+            "edit at {second} overlaps edit at {first}",
+            first = self.first,
+            second = self.second
+        )
+    }
+}
+
+impl error::Error for OverlapError {}
+
+/// A set of non-overlapping [`TextEdit`]s on the same source, sorted by their
+/// spans. Every span in the set refers to the source before any of the edits
+/// are applied.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EditSet {
+    edits: Vec<TextEdit>,
+}
+
+impl From<TextEdit> for EditSet {
+    fn from(edit: TextEdit) -> Self {
+        Self { edits: vec![edit] }
+    }
+}
+
+impl EditSet {
+    /// Creates a new [`EditSet`] from the given edits, returning an
+    /// [`OverlapError`] if any of them overlap.
+    ///
+    /// Edits that only touch do not overlap. Insertions at the same offset are
+    /// applied in the given order, before a replacement that starts there.
+    pub fn new(edits: impl IntoIterator<Item = TextEdit>) -> Result<Self, OverlapError> {
+        let mut edits: Vec<_> = edits.into_iter().collect();
+        edits.sort_by_key(|edit| (edit.span.start, edit.span.end));
+
+        for (first, second) in edits.iter().zip(edits.iter().skip(1)) {
+            if second.span.start < first.span.end {
+                return Err(OverlapError {
+                    first: first.span,
+                    second: second.span,
+                });
+            }
+        }
+
+        Ok(Self { edits })
+    }
+
+    /// Returns the edits, sorted by their spans.
+    pub fn edits(&self) -> &[TextEdit] {
+        &self.edits
+    }
+
+    /// Whether if there are no edits.
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Returns the smallest span that covers every edit, in the source before
+    /// the edits are applied.
+    pub fn span(&self) -> Option<Span> {
+        self.edits.iter().map(|edit| edit.span).reduce(Span::cover)
+    }
+
+    /// Whether if any of the edits overlaps or touches the given span.
+    pub fn touches(&self, span: Span) -> bool {
+        self.edits.iter().any(|edit| {
+            // Spans that only touch, or are empty, have no intersection. Their
+            // cover is exactly as long as both of them then.
+            edit.span.intersect(span).is_some() || edit.span.cover(span).len() == edit.span.len() + span.len()
+        })
+    }
+
+    /// Applies the edits to the given source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the span of an edit is out of the bounds of the source, or
+    /// does not start and end on a char boundary. This is never the case for
+    /// edits of spans from the same source.
+    pub fn apply(&self, source: &str) -> String {
+        let len = self
+            .edits
+            .iter()
+            .fold(source.len() as i64, |len, edit| len + edit.delta());
+
+        let mut result = String::with_capacity(len.max(0) as usize);
+        let mut last = Size::new(0u32);
+
+        for edit in &self.edits {
+            result.push_str(&source[Span::std(last, edit.span.start)]);
+            result.push_str(&edit.replacement);
+
+            last = edit.span.end;
+        }
+
+        result.push_str(&source[usize::from(last)..]);
+        result
+    }
+
+    /// Maps the given offset in the source before the edits are applied to
+    /// the source after.
+    ///
+    /// Offsets inside a replaced span and offsets at an insertion are moved
+    /// to the start or the end of the replacement, depending on the bias. When
+    /// there are multiple insertions at an offset, the end is the end of the
+    /// last one.
+    pub fn map_size(&self, size: impl Into<Size>, bias: Bias) -> Size {
+        let size = size.into();
+        let mut delta = 0;
+
+        for edit in &self.edits {
+            if size < edit.span.start || (size == edit.span.start && !edit.span.is_empty()) {
+                break;
+            }
+
+            if size > edit.span.end || (size == edit.span.end && !edit.span.is_empty()) {
+                delta += edit.delta();
+                continue;
+            }
+
+            let start = Size::new((i64::from(*edit.span.start) + delta) as u32);
+
+            match bias {
+                Bias::Left => return start,
+
+                // The insertions after this one at the same offset, if any,
+                // come after it.
+                Bias::Right if edit.span.is_empty() => delta += edit.delta(),
+
+                Bias::Right => return start + edit.replacement.len(),
+            }
+        }
+
+        Size::new((i64::from(*size) + delta) as u32)
+    }
+
+    /// Maps the given span in the source before the edits are applied to the
+    /// source after, mapping both ends with the given bias. Spans that are
+    /// completely replaced are collapsed to the start or the end of the
+    /// replacement.
+    pub fn map_span(&self, span: impl Into<Span>, bias: Bias) -> Span {
+        let span = span.into();
+
+        Span::new(self.map_size(span.start, bias), self.map_size(span.end, bias))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply() {
+        let edits = EditSet::new([
+            TextEdit::replace(8..11u32, "quux"),
+            TextEdit::insert(0u32, "// "),
+            TextEdit::delete(3..4u32),
+        ])
+        .unwrap();
+
+        assert_eq!(edits.apply("foo bar baz"), "// foobar quux");
+        assert_eq!(edits.span(), Some(Span::new(0u32, 11u32)));
+    }
+
+    #[test]
+    fn overlap() {
+        assert_eq!(
+            EditSet::new([TextEdit::delete(0..5u32), TextEdit::replace(3..8u32, "")]),
+            Err(OverlapError {
+                first: Span::new(0u32, 5u32),
+                second: Span::new(3u32, 8u32),
+            }),
+        );
+
+        assert!(EditSet::new([TextEdit::delete(0..5u32), TextEdit::insert(2u32, "foo")]).is_err());
+
+        // Touching edits do not overlap.
+        assert!(EditSet::new([TextEdit::delete(0..5u32), TextEdit::insert(5u32, "foo")]).is_ok());
+        assert!(EditSet::new([TextEdit::insert(0u32, "foo"), TextEdit::delete(0..5u32)]).is_ok());
+    }
+
+    #[test]
+    fn map() {
+        // "foo bar baz" -> "foo quux baz!"
+        let edits = EditSet::new([TextEdit::replace(4..7u32, "quux"), TextEdit::insert(11u32, "!")]).unwrap();
+
+        assert_eq!(edits.map_size(2u32, Bias::Left), Size::new(2u32));
+        assert_eq!(edits.map_size(4u32, Bias::Right), Size::new(4u32));
+        assert_eq!(edits.map_size(5u32, Bias::Left), Size::new(4u32));
+        assert_eq!(edits.map_size(5u32, Bias::Right), Size::new(8u32));
+        assert_eq!(edits.map_size(7u32, Bias::Left), Size::new(8u32));
+        assert_eq!(edits.map_size(11u32, Bias::Left), Size::new(12u32));
+        assert_eq!(edits.map_size(11u32, Bias::Right), Size::new(13u32));
+
+        assert_eq!(edits.map_span(8..11u32, Bias::Left), Span::new(9u32, 12u32));
+        assert_eq!(edits.map_span(5..6u32, Bias::Right), Span::new(8u32, 8u32));
+    }
+
+    #[test]
+    fn map_stacked_insertions() {
+        // "foo bar" -> "foo(a)(b) baz"
+        let edits = EditSet::new([
+            TextEdit::insert(3u32, "(a)"),
+            TextEdit::insert(3u32, "(b)"),
+            TextEdit::replace(4..7u32, "baz"),
+        ])
+        .unwrap();
+
+        assert_eq!(edits.apply("foo bar"), "foo(a)(b) baz");
+
+        assert_eq!(edits.map_size(3u32, Bias::Left), Size::new(3u32));
+        assert_eq!(edits.map_size(3u32, Bias::Right), Size::new(9u32));
+        assert_eq!(edits.map_size(4u32, Bias::Left), Size::new(10u32));
+        assert_eq!(edits.map_size(5u32, Bias::Right), Size::new(13u32));
+    }
+
+    #[test]
+    fn touches() {
+        let edits = EditSet::new([TextEdit::replace(4..7u32, "baz"), TextEdit::insert(10u32, "!")]).unwrap();
+
+        assert!(edits.touches(Span::new(5u32, 6u32)));
+        assert!(edits.touches(Span::new(0u32, 4u32)));
+        assert!(edits.touches(Span::new(7u32, 7u32)));
+        assert!(edits.touches(Span::new(8u32, 12u32)));
+        assert!(!edits.touches(Span::new(0u32, 3u32)));
+        assert!(!edits.touches(Span::new(8u32, 9u32)));
+    }
+}
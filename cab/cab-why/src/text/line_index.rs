@@ -0,0 +1,271 @@
+use std::cmp;
+
+use crate::{
+    Position,
+    Size,
+    Span,
+};
+
+/// The unit columns are counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Bytes of UTF-8.
+    Utf8,
+    /// Code units of UTF-16. The default of the language server protocol.
+    Utf16,
+    /// Chars, also known as Unicode scalar values.
+    Char,
+}
+
+/// A zero indexed line and column, as used by the language server protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineColumn {
+    /// The line. Zero indexed.
+    pub line: u32,
+    /// The column, in the unit of the [`Encoding`] it was created with. Zero
+    /// indexed.
+    pub column: u32,
+}
+
+/// A non-ASCII char in a source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WideChar {
+    offset: Size,
+    len: u8,
+}
+
+impl WideChar {
+    fn len_in(self, encoding: Encoding) -> u32 {
+        match encoding {
+            Encoding::Utf8 => self.len.into(),
+            Encoding::Utf16 if self.len == 4 => 2,
+            Encoding::Utf16 | Encoding::Char => 1,
+        }
+    }
+}
+
+/// An index of the lines of a source, to convert between byte offsets and
+/// lines and columns without scanning the source every time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    len: Size,
+    /// The offsets of the starts of lines. The first one is always zero.
+    line_starts: Vec<Size>,
+    /// The non-ASCII chars of the source, sorted by their offsets.
+    wide_chars: Vec<WideChar>,
+}
+
+impl LineIndex {
+    /// Creates a new [`LineIndex`] of the given source.
+    pub fn new(source: &str) -> Self {
+        let mut line_starts = vec![Size::new(0u32)];
+        let mut wide_chars = Vec::new();
+
+        for (offset, c) in source.char_indices() {
+            if c == '\n' {
+                line_starts.push(Size::new(offset + 1));
+            } else if !c.is_ascii() {
+                wide_chars.push(WideChar {
+                    offset: Size::new(offset),
+                    len: c.len_utf8() as u8,
+                });
+            }
+        }
+
+        Self {
+            len: Size::new(source.len()),
+            line_starts,
+            wide_chars,
+        }
+    }
+
+    /// Returns the byte length of the indexed source.
+    pub fn len(&self) -> Size {
+        self.len
+    }
+
+    /// Whether if the indexed source is empty.
+    pub fn is_empty(&self) -> bool {
+        *self.len == 0
+    }
+
+    /// Returns the amount of lines. An empty source has a single line.
+    pub fn line_count(&self) -> u32 {
+        self.line_starts.len() as u32
+    }
+
+    /// Returns the span of the given zero indexed line, excluding its newline.
+    pub fn line_span(&self, line: u32) -> Option<Span> {
+        let start = *self.line_starts.get(line as usize)?;
+        let end = self
+            .line_starts
+            .get(line as usize + 1)
+            .map_or(self.len, |&next| next - 1u32);
+
+        Some(Span::new(start, end))
+    }
+
+    /// Returns the zero indexed line the given offset is on. Offsets past the
+    /// end are on the last line.
+    pub fn line_of(&self, offset: impl Into<Size>) -> u32 {
+        let offset = cmp::min(offset.into(), self.len);
+
+        (self.line_starts.partition_point(|&start| start <= offset) - 1) as u32
+    }
+
+    fn wide_chars_in(&self, span: Span) -> &[WideChar] {
+        let start = self.wide_chars.partition_point(|wide| wide.offset < span.start);
+        let end = self.wide_chars.partition_point(|wide| wide.offset < span.end);
+
+        &self.wide_chars[start..end]
+    }
+
+    /// Converts the given offset to a zero indexed line and column counted in
+    /// the given encoding. Offsets past the end are clamped to the end, and
+    /// offsets inside a char are moved to its end.
+    pub fn line_column(&self, offset: impl Into<Size>, encoding: Encoding) -> LineColumn {
+        let mut offset = cmp::min(offset.into(), self.len);
+
+        if let Some(wide) = self.wide_chars_in(Span::up_to(offset)).last()
+            && wide.offset + u32::from(wide.len) > offset
+        {
+            offset = wide.offset + u32::from(wide.len);
+        }
+
+        let line = self.line_of(offset);
+        let line_start = self.line_starts[line as usize];
+
+        let column = self
+            .wide_chars_in(Span::new(line_start, offset))
+            .iter()
+            .fold(*(offset - line_start), |column, &wide| {
+                column - u32::from(wide.len) + wide.len_in(encoding)
+            });
+
+        LineColumn { line, column }
+    }
+
+    /// Converts the given zero indexed line and column counted in the given
+    /// encoding to an offset. Returns [`None`] if the line does not exist, if
+    /// the column is past the end of the line or if it points inside a char.
+    pub fn offset(&self, line_column: LineColumn, encoding: Encoding) -> Option<Size> {
+        let line = self.line_span(line_column.line)?;
+
+        let mut offset = line.start;
+        let mut remaining = line_column.column;
+
+        for &wide in self.wide_chars_in(line) {
+            let narrow = *(wide.offset - offset);
+
+            if remaining <= narrow {
+                break;
+            }
+
+            remaining -= narrow;
+            offset = wide.offset;
+
+            match remaining.checked_sub(wide.len_in(encoding)) {
+                Some(rest) => remaining = rest,
+                None => return None,
+            }
+
+            offset += u32::from(wide.len);
+        }
+
+        let offset = offset + remaining;
+        (offset <= line.end).then_some(offset)
+    }
+
+    /// Calculates the start and end [`Position`] of the given span.
+    ///
+    /// The start is the position of the first char of the span, while the end
+    /// is the position of the last char. An end that is right after a newline
+    /// has a column of zero.
+    pub fn position(&self, span: Span) -> (Position, Position) {
+        if self.is_empty() {
+            return (Position { line: 1, column: 1 }, Position { line: 1, column: 1 });
+        }
+
+        // Spans that end at the start are treated as ending after the first char.
+        let end = match self.wide_chars.first() {
+            _ if *span.end > 0 => span.end,
+            Some(wide) if *wide.offset == 0 => Size::new(u32::from(wide.len)),
+            _ => Size::new(1u32),
+        };
+
+        let start = self.line_column(span.start, Encoding::Char);
+        let end = self.line_column(end, Encoding::Char);
+
+        (
+            Position {
+                line: start.line + 1,
+                column: start.column + 1,
+            },
+            Position {
+                line: end.line + 1,
+                column: end.column,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_column() {
+        let index = LineIndex::new("foo\næ𝔸b\n");
+
+        assert_eq!(index.line_count(), 3);
+        assert_eq!(
+            index.line_column(2u32, Encoding::Utf8),
+            LineColumn { line: 0, column: 2 }
+        );
+        assert_eq!(
+            index.line_column(4u32, Encoding::Utf8),
+            LineColumn { line: 1, column: 0 }
+        );
+
+        // After `æ𝔸`.
+        assert_eq!(
+            index.line_column(10u32, Encoding::Utf8),
+            LineColumn { line: 1, column: 6 }
+        );
+        assert_eq!(
+            index.line_column(10u32, Encoding::Utf16),
+            LineColumn { line: 1, column: 3 }
+        );
+        assert_eq!(
+            index.line_column(10u32, Encoding::Char),
+            LineColumn { line: 1, column: 2 }
+        );
+
+        assert_eq!(
+            index.line_column(12u32, Encoding::Char),
+            LineColumn { line: 2, column: 0 }
+        );
+        assert_eq!(
+            index.line_column(100u32, Encoding::Char),
+            LineColumn { line: 2, column: 0 }
+        );
+    }
+
+    #[test]
+    fn offset() {
+        let index = LineIndex::new("foo\næ𝔸b\n");
+
+        for offset in [0u32, 3, 4, 6, 10, 11, 12] {
+            for encoding in [Encoding::Utf8, Encoding::Utf16, Encoding::Char] {
+                let line_column = index.line_column(offset, encoding);
+                assert_eq!(index.offset(line_column, encoding), Some(Size::new(offset)));
+            }
+        }
+
+        // Inside `𝔸`.
+        assert_eq!(index.offset(LineColumn { line: 1, column: 2 }, Encoding::Utf16), None);
+        // Past the end of the line.
+        assert_eq!(index.offset(LineColumn { line: 0, column: 4 }, Encoding::Char), None);
+        assert_eq!(index.offset(LineColumn { line: 3, column: 0 }, Encoding::Char), None);
+    }
+}
@@ -1,4 +1,7 @@
+mod edit;
+mod line_index;
 mod size;
+mod source;
 mod span;
 
 use std::sync::{
@@ -7,10 +10,26 @@ use std::sync::{
 };
 
 pub use self::{
+    edit::{
+        Bias,
+        EditSet,
+        OverlapError,
+        TextEdit,
+    },
+    line_index::{
+        Encoding,
+        LineColumn,
+        LineIndex,
+    },
     size::{
         IntoSize,
         Size,
     },
+    source::{
+        Source,
+        SourceId,
+        SourceMap,
+    },
     span::{
         IntoSpan,
         Span,
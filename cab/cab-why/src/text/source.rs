@@ -0,0 +1,142 @@
+use std::{
+    fmt,
+    ops,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use crate::LineIndex;
+
+/// The identifier of a [`Source`] in a [`SourceMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SourceId(u32);
+
+/// A source file, with the name it is displayed with.
+#[derive(Debug, Clone)]
+pub struct Source {
+    name: String,
+    content: String,
+    path: Option<PathBuf>,
+    index: LineIndex,
+}
+
+impl Source {
+    /// Creates a new [`Source`] with the given name and content, building the
+    /// [`LineIndex`] of the content.
+    pub fn new(name: impl fmt::Display, content: impl Into<String>) -> Self {
+        let content = content.into();
+
+        Self {
+            name: name.to_string(),
+            index: LineIndex::new(&content),
+            content,
+            path: None,
+        }
+    }
+
+    /// Returns the name of the source. Usually the display of the island entry
+    /// it was read from.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the content of the source.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Returns the path of the source on the local filesystem, if it was read
+    /// from one. Used to hyperlink the headers of reports.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Returns the [`LineIndex`] of the content.
+    pub fn index(&self) -> &LineIndex {
+        &self.index
+    }
+}
+
+/// A list of sources that can be referred to by [`SourceId`]s, in insertion
+/// order. Sources can also be found by name with [`SourceMap::find`], which
+/// searches them linearly.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    sources: Vec<Source>,
+}
+
+impl ops::Index<SourceId> for SourceMap {
+    type Output = Source;
+
+    fn index(&self, id: SourceId) -> &Self::Output {
+        &self.sources[id.0 as usize]
+    }
+}
+
+impl SourceMap {
+    /// Creates a new empty [`SourceMap`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a source with the given name and content, returning its
+    /// [`SourceId`].
+    ///
+    /// Sources are never replaced, so spans made against a source stay valid.
+    /// Inserting a name that already exists adds a new source with a new
+    /// [`SourceId`], which shadows the old one in [`SourceMap::find`].
+    pub fn insert(&mut self, name: impl fmt::Display, content: impl Into<String>) -> SourceId {
+        self.sources.push(Source::new(name, content));
+
+        SourceId((self.sources.len() - 1).try_into().expect("too many sources"))
+    }
+
+    /// Sets the local filesystem path of the source with the given
+    /// [`SourceId`].
+    pub fn set_path(&mut self, id: SourceId, path: impl Into<PathBuf>) {
+        self.sources[id.0 as usize].path = Some(path.into());
+    }
+
+    /// Returns the [`SourceId`] of the latest source with the given name.
+    pub fn find(&self, name: &str) -> Option<SourceId> {
+        self.sources
+            .iter()
+            .rposition(|source| source.name == name)
+            .map(|index| SourceId(index as u32))
+    }
+
+    /// Returns the source with the given [`SourceId`].
+    pub fn get(&self, id: SourceId) -> Option<&Source> {
+        self.sources.get(id.0 as usize)
+    }
+
+    /// Iterates over all sources along with their [`SourceId`]s.
+    pub fn iter(&self) -> impl Iterator<Item = (SourceId, &Source)> {
+        self.sources
+            .iter()
+            .enumerate()
+            .map(|(index, source)| (SourceId(index as u32), source))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_does_not_replace() {
+        let mut sources = SourceMap::new();
+
+        let old = sources.insert("foo", "old content");
+        let new = sources.insert("foo", "new");
+
+        assert_ne!(old, new);
+        assert_eq!(sources[old].content(), "old content");
+        assert_eq!(sources[new].content(), "new");
+        assert_eq!(sources[new].index().line_count(), 1);
+        assert_eq!(sources.find("foo"), Some(new));
+        assert_eq!(sources.find("bar"), None);
+    }
+}
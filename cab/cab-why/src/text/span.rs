@@ -89,7 +89,7 @@ impl Span {
     /// Returns the len of this span.
     #[inline]
     pub fn len(self) -> Size {
-        self.start - self.end
+        self.end - self.start
     }
 
     /// Whether or not this span has a len of 0.
@@ -1,9 +1,15 @@
 use std::{
+    collections::HashSet,
+    env,
+    fmt,
+    fs,
     io::{
         self,
         Write as _,
     },
+    mem,
     path::{
+        self,
         Path,
         PathBuf,
     },
@@ -24,10 +30,56 @@ use yansi::Paint as _;
 #[derive(clap::Parser)]
 #[command(version, about)]
 struct Cli {
+    /// The format to write reports to stderr in.
+    #[arg(long, global = true, value_enum, default_value_t = MessageFormat::Human)]
+    message_format: MessageFormat,
+
+    /// The maximum amount of errors to report. The rest are omitted.
+    #[arg(long, global = true)]
+    max_errors: Option<usize>,
+
+    /// The glyphs to draw human readable reports with.
+    #[arg(long, global = true, value_enum, default_value_t = ReportGlyphs::Unicode)]
+    glyphs: ReportGlyphs,
+
+    /// The color theme of human readable reports.
+    #[arg(long, global = true, value_enum, default_value_t = ReportTheme::Dark)]
+    theme: ReportTheme,
+
     #[command(subcommand)]
     command: Command,
 }
 
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum MessageFormat {
+    /// Reports with annotated source snippets.
+    Human,
+    /// A line per label, as `location:line:column: severity: title: label`.
+    Short,
+    /// A JSON object per report, one per line.
+    Json,
+    /// A single SARIF log containing all reports.
+    Sarif,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum ReportGlyphs {
+    /// Unicode box-drawing characters.
+    Unicode,
+    /// ASCII characters only.
+    Ascii,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum ReportTheme {
+    /// Colors for dark terminal backgrounds.
+    Dark,
+    /// Colors for light terminal backgrounds.
+    Light,
+    /// No colors.
+    Monochrome,
+}
+
 #[derive(clap::Subcommand, Debug, Clone)]
 enum Command {
     /// Various commands related to debugging.
@@ -39,6 +91,58 @@ enum Command {
         #[clap(default_value = "-", global = true)]
         path: PathBuf,
     },
+
+    /// Print the provided file with syntax highlighting. Colored with the
+    /// theme of reports.
+    Highlight {
+        /// The format to highlight in.
+        #[arg(long, value_enum, default_value_t = HighlightFormat::Ansi)]
+        format: HighlightFormat,
+
+        /// The file to highlight. If set to '-', stdin is read.
+        #[clap(default_value = "-")]
+        path: PathBuf,
+    },
+
+    /// Print the long-form explanation of a report code.
+    Explain {
+        /// The code to explain, such as E0012.
+        code: String,
+    },
+
+    /// Apply all machine-applicable suggestions to the provided file.
+    Fix {
+        /// The file to fix.
+        path: PathBuf,
+    },
+
+    /// Print the nodes that match a selector, such as
+    /// 'NODE_ATTRIBUTES > NODE_INFIX_OPERATION[operator=Equal]'.
+    Query {
+        /// The selector to match nodes with.
+        selector: String,
+
+        /// The files to query. If set to '-', stdin is read.
+        #[clap(default_value = "-")]
+        paths: Vec<PathBuf>,
+    },
+
+    /// Generate documentation of the attributes in the provided files from
+    /// the comments before their bindings.
+    Doc {
+        /// The format to generate documentation in.
+        #[arg(long, value_enum, default_value_t = DocFormat::Markdown)]
+        format: DocFormat,
+
+        /// The directory to write a page per file to, named after the path of
+        /// the file. If not specified, the pages are printed.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// The files to document. If set to '-', stdin is read.
+        #[clap(default_value = "-")]
+        paths: Vec<PathBuf>,
+    },
 }
 
 #[derive(clap::Subcommand, Debug, Clone, Copy)]
@@ -48,23 +152,71 @@ enum Dump {
         /// If specified, the output will be colored instead of typed.
         #[arg(long, short, global = true)]
         color: bool,
+
+        /// If specified, the line and column range of every token is printed
+        /// before it.
+        #[arg(long, short, conflicts_with = "color")]
+        positions: bool,
     },
 
     /// Dump the provided file's syntax.
-    Syntax,
+    Syntax {
+        /// The format to dump the syntax in.
+        #[arg(long, value_enum, default_value_t = SyntaxFormat::Debug)]
+        format: SyntaxFormat,
+    },
 
     /// Dump the provided file with parentheses to disambiguate.
     Parenthesize,
 }
 
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum SyntaxFormat {
+    /// An indented tree of nodes and tokens.
+    Debug,
+    /// A JSON object of the root node, with every node and token nested in
+    /// the children of their parents.
+    Json,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum HighlightFormat {
+    /// ANSI escapes.
+    Ansi,
+    /// A standalone HTML page, with CSS classes for the highlighted tokens.
+    Html,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum DocFormat {
+    /// A Markdown page per file.
+    Markdown,
+    /// A standalone HTML page per file.
+    Html,
+}
+
 #[tokio::main]
 async fn main() -> why::Termination {
     let cli = Cli::parse();
 
     yansi::whenever(yansi::Condition::TTY_AND_COLOR);
 
-    let report = cab::why::Report::error("foo").primary(0u32..1u32, "bar");
-    Err(report.with("a", "b")).context("foo")?;
+    why::install_panic_hook(concat!(env!("CARGO_PKG_REPOSITORY"), "/issues"));
+
+    let config = why::Config {
+        glyphs: match cli.glyphs {
+            ReportGlyphs::Unicode => why::Glyphs::UNICODE,
+            ReportGlyphs::Ascii => why::Glyphs::ASCII,
+        },
+
+        theme: match cli.theme {
+            ReportTheme::Dark => why::Theme::DARK,
+            ReportTheme::Light => why::Theme::LIGHT,
+            ReportTheme::Monochrome => why::Theme::MONOCHROME,
+        },
+
+        ..why::Config::DEFAULT
+    };
 
     let (mut out, mut err) = (io::stdout(), io::stderr());
 
@@ -76,22 +228,30 @@ async fn main() -> why::Termination {
                 Arc::new(island::fs(path))
             };
 
-            let source = leaf.clone().read().await?.to_vec();
+            let _processing = why::processing(island::display!(leaf));
 
-            let source = String::from_utf8(source).with_context(|| {
-                format!(
-                    "failed to convert {leaf} to an UTF-8 string",
-                    leaf = island::display!(leaf)
-                )
-            })?;
+            let source = read(leaf.clone()).await?;
 
             match command {
-                Dump::Token { color } => {
-                    for (kind, slice) in syntax::tokenize(&source) {
+                Dump::Token { color, positions } => {
+                    let index = why::LineIndex::new(&source);
+
+                    for (kind, slice, span) in syntax::tokenize_spanned(&source) {
                         if color {
                             let style = syntax::COLORS[kind as usize];
 
                             write!(out, "{slice}", slice = slice.paint(style))
+                        } else if positions {
+                            let (start, end) = index.position(span);
+
+                            writeln!(
+                                out,
+                                "{start_line}:{start_column}-{end_line}:{end_column} {kind:?} {slice:?}",
+                                start_line = start.line,
+                                start_column = start.column,
+                                end_line = end.line,
+                                end_column = end.column,
+                            )
                         } else {
                             writeln!(out, "{kind:?} {slice:?}")
                         }
@@ -99,24 +259,458 @@ async fn main() -> why::Termination {
                     }
                 },
 
-                Dump::Syntax | Dump::Parenthesize => {
+                Dump::Syntax { .. } | Dump::Parenthesize => {
                     let oracle = syntax::oracle();
-                    let parse = oracle.parse(syntax::tokenize(&source));
+                    let mut parse = oracle.parse(syntax::tokenize(&source));
 
-                    for report in parse.reports {
-                        writeln!(err, "{report}", report = report.with(island::display!(leaf), &source)).ok();
+                    let reports = mem::take(&mut parse.reports);
+                    let reports = why::Postprocessor {
+                        max_errors: cli.max_errors,
                     }
+                    .process(reports, |report| parse.node_of(report));
 
-                    if let Dump::Syntax = command {
-                        write!(out, "{node:#?}", node = parse.node)
-                    } else {
-                        syntax::format::parenthesize(&mut out, parse.expression.as_ref())
+                    write_reports(
+                        &mut err,
+                        cli.message_format,
+                        config,
+                        reports,
+                        island::display!(leaf),
+                        leaf.local_path(),
+                        &source,
+                    )
+                    .ok();
+
+                    match command {
+                        Dump::Syntax {
+                            format: SyntaxFormat::Debug,
+                        } => write!(out, "{node:#?}", node = parse.node),
+
+                        Dump::Syntax {
+                            format: SyntaxFormat::Json,
+                        } => writeln!(out, "{json}", json = syntax::format::json(&parse.node)),
+
+                        _ => syntax::format::parenthesize(&mut out, parse.expression.as_ref()),
                     }
                     .context("failed to write to stdout")?;
                 },
             }
         },
+
+        Command::Highlight { format, path } => {
+            let leaf: Arc<dyn island::Leaf> = if path == Path::new("-") {
+                Arc::new(island::stdin())
+            } else {
+                Arc::new(island::fs(path))
+            };
+
+            let _processing = why::processing(island::display!(leaf));
+
+            let source = read(leaf.clone()).await?;
+
+            let parse = syntax::oracle().parse(syntax::tokenize(&source));
+
+            match format {
+                HighlightFormat::Ansi => {
+                    let theme = match cli.theme {
+                        ReportTheme::Dark => syntax::highlight::Theme::DARK,
+                        ReportTheme::Light => syntax::highlight::Theme::LIGHT,
+                        ReportTheme::Monochrome => syntax::highlight::Theme::MONOCHROME,
+                    };
+
+                    syntax::highlight::ansi(&mut out, &parse.node, &theme)
+                },
+
+                HighlightFormat::Html => {
+                    syntax::highlight::html_page(&mut out, &parse.node, &island::display!(leaf).to_string())
+                },
+            }
+            .context("failed to write to stdout")?;
+        },
+
+        Command::Explain { code } => {
+            let lines = syntax::code::explain(&code.to_ascii_uppercase())
+                .with_context(|| format!("unknown report code '{code}'"))?;
+
+            for line in lines {
+                writeln!(out, "{line}").context("failed to write to stdout")?;
+            }
+        },
+
+        Command::Fix { path } => {
+            let leaf: Arc<dyn island::WritableLeaf> = Arc::new(island::fs(path));
+
+            let _processing = why::processing(island::display!(leaf));
+
+            let mut source = read(leaf.clone()).await?;
+
+            let oracle = syntax::oracle();
+            let mut fixed = 0;
+
+            // Fixes may uncover further fixes, so reparse until there is nothing left to
+            // apply. Bounded in case a fix does not make its report go away.
+            for _ in 0..FIX_PASSES_MAX {
+                let parse = oracle.parse(syntax::tokenize(&source));
+
+                let suggestions = parse
+                    .reports
+                    .into_iter()
+                    .flat_map(|report| report.suggestions)
+                    .filter(|suggestion| suggestion.applicability == why::Applicability::MachineApplicable)
+                    .filter(|suggestion| suggestion.source.is_none());
+
+                match apply(&mut source, suggestions) {
+                    Ok(0) => break,
+                    Ok(count) => fixed += count,
+
+                    Err(report) => {
+                        write_reports(
+                            &mut err,
+                            cli.message_format,
+                            config,
+                            vec![report],
+                            island::display!(leaf),
+                            leaf.local_path(),
+                            &source,
+                        )
+                        .ok();
+
+                        return why::Termination::error(why::error!(
+                            "failed to fix {leaf}",
+                            leaf = island::display!(leaf),
+                        ));
+                    },
+                }
+            }
+
+            if fixed > 0 {
+                leaf.clone().write(source.into()).await?;
+            }
+
+            writeln!(
+                err,
+                "{applied} {fixed} {fixes} to {leaf}",
+                applied = "applied".green().bold(),
+                fixes = if fixed == 1 { "fix" } else { "fixes" },
+                leaf = island::display!(leaf),
+            )
+            .ok();
+        },
+
+        Command::Query { selector, paths } => {
+            let selector = match selector.parse::<syntax::query::Selector>() {
+                Ok(selector) => selector,
+
+                Err(report) => {
+                    write_reports(
+                        &mut err,
+                        cli.message_format,
+                        config,
+                        vec![report],
+                        "<selector>",
+                        None,
+                        &selector,
+                    )
+                    .ok();
+
+                    return why::Termination::error(why::error!("invalid selector '{selector}'"));
+                },
+            };
+
+            let oracle = syntax::oracle();
+
+            for path in paths {
+                let leaf: Arc<dyn island::Leaf> = if path == Path::new("-") {
+                    Arc::new(island::stdin())
+                } else {
+                    Arc::new(island::fs(path))
+                };
+
+                let _processing = why::processing(island::display!(leaf));
+
+                let source = read(leaf.clone()).await?;
+                let index = why::LineIndex::new(&source);
+
+                let parse = oracle.parse(syntax::tokenize(&source));
+
+                for node in selector.select(&parse.node) {
+                    let span = trim(&source, why::Span::from(node.text_range()));
+                    let (start, _) = index.position(span);
+
+                    writeln!(
+                        out,
+                        "{leaf}:{line}:{column}: {kind}",
+                        leaf = island::display!(leaf),
+                        line = start.line,
+                        column = start.column,
+                        kind = format!("{kind:?}", kind = node.kind()).bold(),
+                    )
+                    .context("failed to write to stdout")?;
+
+                    write_snippet(&mut out, &source, &index, span).context("failed to write to stdout")?;
+                }
+            }
+        },
+
+        Command::Doc { format, output, paths } => {
+            let output: Option<Arc<dyn island::WritableCollection>> = match output {
+                Some(output) => {
+                    fs::create_dir_all(&output)
+                        .with_context(|| format!("failed to create {output}", output = output.display()))?;
+
+                    Some(Arc::new(island::fs(output)))
+                },
+
+                None => None,
+            };
+
+            let oracle = syntax::oracle();
+
+            let mut names = HashSet::new();
+            let mut failed = 0;
+
+            for path in paths {
+                let (leaf, name): (Arc<dyn island::Leaf>, _) = if path == Path::new("-") {
+                    (Arc::new(island::stdin()), "stdin".to_owned())
+                } else {
+                    let name = page_name(&path);
+
+                    (Arc::new(island::fs(path)), name)
+                };
+
+                let _processing = why::processing(island::display!(leaf));
+
+                // Pages are only named after the paths of files when they are written.
+                if output.is_some() && !names.insert(name.clone()) {
+                    writeln!(
+                        err,
+                        "{header} the page of {leaf} would overwrite the page '{name}' of an earlier file",
+                        header = "error:".red().bold(),
+                        leaf = island::display!(leaf),
+                    )
+                    .ok();
+
+                    failed += 1;
+                    continue;
+                }
+
+                let source = match read(leaf.clone()).await {
+                    Ok(source) => source,
+
+                    Err(error) => {
+                        write!(err, "{error:?}").ok();
+
+                        failed += 1;
+                        continue;
+                    },
+                };
+
+                let mut parse = oracle.parse(syntax::tokenize(&source));
+
+                let reports = mem::take(&mut parse.reports);
+                let reports = why::Postprocessor {
+                    max_errors: cli.max_errors,
+                }
+                .process(reports, |report| parse.node_of(report));
+
+                write_reports(
+                    &mut err,
+                    cli.message_format,
+                    config,
+                    reports,
+                    island::display!(leaf),
+                    leaf.local_path(),
+                    &source,
+                )
+                .ok();
+
+                let items = syntax::doc::items(parse.expression.as_ref());
+                let title = island::display!(leaf).to_string();
+
+                let mut page = Vec::new();
+
+                let extension = match format {
+                    DocFormat::Markdown => {
+                        syntax::doc::markdown(&mut page, &title, &items).expect("writing to a vec must not fail");
+                        "md"
+                    },
+
+                    DocFormat::Html => {
+                        syntax::doc::html(&mut page, &title, &items).expect("writing to a vec must not fail");
+                        "html"
+                    },
+                };
+
+                match &output {
+                    Some(output) => {
+                        let written = output
+                            .clone()
+                            .write_leaf(&format!("{name}.{extension}"), page.into())
+                            .await;
+
+                        if let Err(error) = written {
+                            write!(err, "{error:?}").ok();
+                            failed += 1;
+                        }
+                    },
+
+                    None => out.write_all(&page).context("failed to write to stdout")?,
+                }
+            }
+
+            if failed > 0 {
+                return why::Termination::error(why::error!(
+                    "failed to document {failed} {files}",
+                    files = if failed == 1 { "file" } else { "files" },
+                ));
+            }
+        },
     }
 
     why::Termination::success()
 }
+
+fn write_reports(
+    writer: &mut impl io::Write,
+    format: MessageFormat,
+    config: why::Config,
+    reports: Vec<why::Report>,
+    location: impl fmt::Display,
+    path: Option<PathBuf>,
+    source: &str,
+) -> io::Result<()> {
+    let index = why::LineIndex::new(source);
+
+    match format {
+        MessageFormat::Human => {
+            for report in reports {
+                let mut report = report.with_index(&location, source, &index).config(config);
+
+                if let Some(path) = &path {
+                    report = report.path(path);
+                }
+
+                writeln!(writer, "{report}")?;
+            }
+        },
+
+        MessageFormat::Short => {
+            for report in reports {
+                write!(
+                    writer,
+                    "{report}",
+                    report = report.short(&location, &index).config(config)
+                )?;
+            }
+        },
+
+        MessageFormat::Json => {
+            for report in reports {
+                writeln!(writer, "{report}", report = report.json(path.as_deref(), &index))?;
+            }
+        },
+
+        MessageFormat::Sarif => {
+            let results = reports.iter().map(|report| report.sarif(path.as_deref(), &index));
+
+            writeln!(
+                writer,
+                "{log}",
+                log = why::sarif_log(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), results)
+            )?;
+        },
+    }
+
+    Ok(())
+}
+
+const FIX_PASSES_MAX: usize = 16;
+
+const SNIPPET_LINES_MAX: u32 = 4;
+
+/// Shrinks the span to exclude the whitespace at its ends.
+fn trim(source: &str, span: why::Span) -> why::Span {
+    let text = &source[span.as_std()];
+    let start = span.start + (text.len() - text.trim_start().len());
+
+    why::Span::new(start, start + text.trim().len())
+}
+
+/// Writes the lines the span is on, indented. Lines after the first
+/// [`SNIPPET_LINES_MAX`] are omitted.
+fn write_snippet(writer: &mut impl io::Write, source: &str, index: &why::LineIndex, span: why::Span) -> io::Result<()> {
+    let first = index.line_of(span.start);
+    let last = index.line_of(span.end);
+
+    for line in first..=last.min(first + SNIPPET_LINES_MAX - 1) {
+        let line_span = index.line_span(line).expect("line of an offset must exist");
+
+        writeln!(writer, "    {text}", text = &source[line_span.as_std()])?;
+    }
+
+    if last - first >= SNIPPET_LINES_MAX {
+        writeln!(
+            writer,
+            "    {omitted}",
+            omitted = format!("... {count} more lines", count = last - first + 1 - SNIPPET_LINES_MAX).dim(),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Returns the name of the documentation page of the file at the given path,
+/// which is the path relative to the working directory without the extension,
+/// with its components joined by `-`.
+fn page_name(path: &Path) -> String {
+    let cwd = env::current_dir().ok();
+
+    let path = cwd
+        .as_deref()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path)
+        .with_extension("");
+
+    path.components()
+        .filter_map(|component| {
+            match component {
+                path::Component::Normal(name) => Some(name.to_string_lossy()),
+                _ => None,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+async fn read(leaf: Arc<dyn island::Leaf>) -> why::Result<String> {
+    let source = leaf.clone().read().await?.to_vec();
+
+    String::from_utf8(source).with_context(|| {
+        format!(
+            "failed to convert {leaf} to an UTF-8 string",
+            leaf = island::display!(leaf)
+        )
+    })
+}
+
+/// Applies the given suggestions to the source. Returns the amount of applied
+/// suggestions, or a report if any of them overlap, in which case the source is
+/// left as is. Duplicate suggestions are applied once.
+fn apply(source: &mut String, suggestions: impl Iterator<Item = why::Suggestion>) -> Result<usize, why::Report> {
+    let mut edits: Vec<_> = suggestions
+        .map(|suggestion| why::TextEdit::replace(suggestion.span, suggestion.replacement))
+        .collect();
+
+    edits.sort_by_key(|edit| edit.span);
+    edits.dedup();
+
+    let edits = why::EditSet::new(edits).map_err(|error| {
+        why::Report::error("fix suggestions overlap")
+            .primary(error.second, "this fix")
+            .secondary(error.first, "overlaps this fix")
+            .help("apply one of the fixes by hand and run the command again")
+    })?;
+
+    *source = edits.apply(source);
+
+    Ok(edits.edits().len())
+}
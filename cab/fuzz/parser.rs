@@ -29,7 +29,10 @@ fuzz_target!(|source: &str| -> Corpus {
     let island: Arc<dyn island::Leaf> = Arc::new(island::blob(source.to_owned()));
 
     for report in &parse.reports {
-        println!("{report}", report = report.with(island::display!(island), source));
+        println!(
+            "{report}",
+            report = report.clone().with(island::display!(island), source)
+        );
     }
 
     let Ok("true" | "1") = env::var("FUZZ_PARSER_SAVE_VALID").as_deref() else {
@@ -0,0 +1,34 @@
+#![no_main]
+
+use cab::{
+    syntax,
+    why::TextEdit,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (&str, u16, u16, &str)| {
+    let (source, start, len, replacement) = input;
+
+    let mut start = usize::from(start).min(source.len());
+    while !source.is_char_boundary(start) {
+        start -= 1;
+    }
+
+    let mut end = start.saturating_add(usize::from(len)).min(source.len());
+    while !source.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let oracle = syntax::oracle();
+    let parse = oracle.parse(syntax::tokenize(source));
+
+    let reparse = oracle.reparse(&parse, &TextEdit::replace(start..end, replacement.to_owned()));
+
+    let mut source = source.to_owned();
+    source.replace_range(start..end, replacement);
+
+    let parse = oracle.parse(syntax::tokenize(&source));
+
+    assert_eq!(format!("{:#?}", reparse.node), format!("{:#?}", parse.node));
+    assert_eq!(format!("{:?}", reparse.reports), format!("{:?}", parse.reports));
+});